@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use serde::{ Serialize, Deserialize };
+
+use crate::config::EngineConfig;
+use crate::entities::{ CardKind, Match, Severity, TeamWithPlayers };
+
+/// Matches a second yellow or too many yellow cards ban a player for.
+const SHORT_BAN: u32 = 1;
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum AbsenceReason
+{
+    Injury,
+    Suspension
+}
+
+/// A spell in which a player can't be picked, because of an injury or a ban.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Absence
+{
+    pub player_id: u32,
+    pub team_id: u32,
+    pub reason: AbsenceReason,
+    /// How bad the injury is, only set for injuries.
+    pub severity: Option<Severity>,
+    /// Matchday of the match the injury or the card happened in.
+    pub matchday: u32,
+    /// Day of the season that match was played on.
+    pub day: u32,
+    /// Days out for injuries, matches banned for suspensions.
+    pub length: u32,
+    pub matches_missed: u32
+}
+
+impl Absence
+{
+    fn is_active(&self, day: u32) -> bool
+    {
+        match self.reason
+        {
+            AbsenceReason::Injury => day < self.day + self.length,
+            AbsenceReason::Suspension => self.matches_missed < self.length
+        }
+    }
+}
+
+/// Injuries and bans over a season, and the yellow cards that lead to bans.
+#[derive(Clone, Default, Debug)]
+pub struct Absences
+{
+    absences: Vec<Absence>,
+    yellow_cards: HashMap<u32, u32>
+}
+
+impl Absences
+{
+    /// Players of `team_id` who can't be picked for its match on `day`. Every
+    /// one of them is counted as missing that match.
+    pub fn miss_match(&mut self, team_id: u32, day: u32) -> Vec<u32>
+    {
+        let mut unavailable: Vec<u32> = Vec::new();
+
+        for absence in self.absences.iter_mut().filter(|absence| absence.team_id == team_id)
+        {
+            if absence.is_active(day)
+            {
+                absence.matches_missed += 1;
+                unavailable.push(absence.player_id);
+            }
+        }
+
+        return unavailable;
+    }
+
+    /// Start the absences caused by the injuries and cards of a match.
+    pub fn record(&mut self, game: &Match, day: u32, config: &EngineConfig)
+    {
+        for injury in &game.injuries
+        {
+            self.absences.push(Absence {
+                player_id: injury.player_id,
+                team_id: injury.team_id,
+                reason: AbsenceReason::Injury,
+                severity: Some(injury.severity),
+                matchday: game.matchday,
+                day,
+                length: injury.days_out,
+                matches_missed: 0
+            });
+        }
+
+        for card in &game.cards
+        {
+            let ban: u32 = match card.kind
+            {
+                CardKind::Red => config.red_card_ban,
+                CardKind::SecondYellow => SHORT_BAN,
+                CardKind::Yellow =>
+                {
+                    let yellow_cards = self.yellow_cards.entry(card.player_id).or_insert(0);
+                    *yellow_cards += 1;
+
+                    if config.yellow_cards_for_ban == 0 || *yellow_cards < config.yellow_cards_for_ban
+                    {
+                        continue;
+                    }
+
+                    *yellow_cards = 0;
+                    SHORT_BAN
+                }
+            };
+
+            self.absences.push(Absence {
+                player_id: card.player_id,
+                team_id: card.team_id,
+                reason: AbsenceReason::Suspension,
+                severity: None,
+                matchday: game.matchday,
+                day,
+                length: ban,
+                matches_missed: 0
+            });
+        }
+    }
+
+    /// Every absence of the season, in the order they started.
+    pub fn into_report(self) -> Vec<Absence>
+    {
+        return self.absences;
+    }
+}
+
+/// Print injuries, suspensions and matches missed per team, then the longest absences.
+pub fn print_report(teams: &[TeamWithPlayers], absences: &[Absence])
+{
+    const LONGEST: usize = 10;
+
+    println!("{:<25} {:>8} {:>11} {:>7}", "Team", "Injuries", "Suspensions", "Missed");
+
+    for team in teams
+    {
+        let team_absences: Vec<&Absence> = absences.iter().filter(|absence| absence.team_id == team.team.id).collect();
+        let injuries = team_absences.iter().filter(|absence| absence.reason == AbsenceReason::Injury).count();
+        let suspensions = team_absences.len() - injuries;
+        let missed: u32 = team_absences.iter().map(|absence| absence.matches_missed).sum();
+
+        println!("{:<25} {:>8} {:>11} {:>7}", team.team.name, injuries, suspensions, missed);
+    }
+
+    let mut longest: Vec<&Absence> = absences.iter().filter(|absence| absence.matches_missed > 0).collect();
+    longest.sort_by_key(|absence| std::cmp::Reverse(absence.matches_missed));
+
+    println!();
+
+    for absence in longest.into_iter().take(LONGEST)
+    {
+        let name: String = teams.iter()
+            .flat_map(|team| team.players.iter())
+            .find(|player| player.id == absence.player_id)
+            .map(|player| player.name.clone())
+            .unwrap_or_default();
+
+        let reason: String = match absence.severity
+        {
+            Some(severity) => format!("{:?} injury", severity),
+            None => format!("{:?}", absence.reason)
+        };
+
+        println!("{:<25} {:<18} matchday {:>2}, missed {}", name, reason, absence.matchday, absence.matches_missed);
+    }
+}
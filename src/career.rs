@@ -0,0 +1,307 @@
+use chrono::{ Datelike, Duration, NaiveDate };
+use rand::Rng;
+use serde::{ Serialize, Deserialize };
+
+use crate::config::EngineConfig;
+use crate::divisions::{ self, PlayoffBracket, Pyramid, PyramidSeason, TeamMove };
+use crate::entities::{ Attributes, Player, Position, TeamStrength, TeamWithPlayers };
+use crate::error::Result;
+use crate::rng;
+use crate::squad::SquadConfig;
+use crate::table::{ LeagueTable, Tiebreaker };
+
+/// Stream, per season, for the generator that ages, retires and recruits players.
+const DEVELOPMENT_STREAM: u64 = u64::MAX;
+/// Age assumed for players without a known date of birth, they stay around
+/// their level and never retire.
+const PEAK_AGE: u32 = 26;
+/// Keepers peak and decline this many years later than outfield players.
+const GOALKEEPER_AGE_SHIFT: u32 = 2;
+/// Players this old always retire.
+const RETIREMENT_AGE: u32 = 35;
+/// Players from this age on may retire, more likely every year.
+const EARLIEST_RETIREMENT_AGE: u32 = 32;
+/// Players from 30 with a rating below this retire.
+const RETIREMENT_RATING: u8 = 45;
+
+#[derive(Clone, Debug)]
+pub struct CareerConfig
+{
+    pub seasons: u32,
+    /// Start of the first season, ages are taken from it.
+    pub start: NaiveDate,
+    /// Squad sizes youth intakes fill back up to.
+    pub squad: SquadConfig,
+    pub tiebreakers: Vec<Tiebreaker>,
+    pub engine: EngineConfig
+}
+
+impl Default for CareerConfig
+{
+    fn default() -> Self
+    {
+        CareerConfig {
+            seasons: 5,
+            start: NaiveDate::from_ymd_opt(2021, 8, 1).unwrap(),
+            squad: SquadConfig::default(),
+            tiebreakers: crate::table::DEFAULT_TIEBREAKERS.to_vec(),
+            engine: EngineConfig::default()
+        }
+    }
+}
+
+/// A player joining or leaving a team between seasons.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SquadChange
+{
+    pub team_id: u32,
+    pub player: Player
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DivisionTable
+{
+    pub name: String,
+    pub table: LeagueTable
+}
+
+/// One season of a career and the squad changes after it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CareerSeason
+{
+    pub start: NaiveDate,
+    /// Final tables, top division first.
+    pub divisions: Vec<DivisionTable>,
+    pub playoffs: Vec<PlayoffBracket>,
+    /// Teams promoted and relegated after the season.
+    pub moves: Vec<TeamMove>,
+    pub retired: Vec<SquadChange>,
+    pub youth: Vec<SquadChange>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Career
+{
+    pub seed: u64,
+    pub seasons: Vec<CareerSeason>,
+    /// Squads after the last season.
+    pub squads: Vec<TeamWithPlayers>,
+    pub strengths: Vec<TeamStrength>,
+    /// Divisions for the season after the last one.
+    pub pyramid: Pyramid
+}
+
+/// Play `config.seasons` seasons in a row over the divisions of `pyramid`,
+/// moving teams between them. Between seasons every player ages a year and
+/// develops or declines with it, old players retire and youth players fill
+/// the squads back up. Team strengths follow the change in average squad rating.
+pub fn run_career(teams: &[TeamWithPlayers], strengths: &[TeamStrength], pyramid: &Pyramid, config: &CareerConfig, seed: u64) -> Result<Career>
+{
+    let mut pyramid: Pyramid = pyramid.clone();
+    let mut squads: Vec<TeamWithPlayers> = teams.to_vec();
+    let mut strengths: Vec<TeamStrength> = strengths.to_vec();
+    let mut next_id: u32 = squads.iter().flat_map(|team| team.players.iter()).map(|player| player.id + 1).max().unwrap_or(0);
+    let mut seasons: Vec<CareerSeason> = Vec::new();
+
+    for season in 0..config.seasons
+    {
+        let start: NaiveDate = season_start(config.start, season);
+        let season_seed: u64 = rng::derive_seed(seed, season as u64);
+
+        let played: PyramidSeason = divisions::play_season(&pyramid, &squads, &strengths, &config.engine, &config.tiebreakers, season_seed)?;
+        let division_tables: Vec<DivisionTable> = played.divisions.into_iter()
+            .map(|division| DivisionTable { name: division.name, table: division.table })
+            .collect();
+        pyramid = played.next;
+
+        let next_start: NaiveDate = season_start(config.start, season + 1);
+        let mut development_rng = rng::from_seed(rng::derive_seed(season_seed, DEVELOPMENT_STREAM));
+        let mut retired: Vec<SquadChange> = Vec::new();
+        let mut youth: Vec<SquadChange> = Vec::new();
+        let names: Vec<String> = squads.iter().flat_map(|team| team.players.iter()).map(|player| player.name.clone()).collect();
+
+        for team in squads.iter_mut()
+        {
+            let rating_before: f32 = average_rating(&team.players);
+
+            for player in team.players.iter_mut()
+            {
+                develop(player, next_start, &mut development_rng);
+            }
+
+            let (staying, leaving): (Vec<Player>, Vec<Player>) = team.players.drain(..)
+                .partition(|player| !retires(player, next_start, &mut development_rng));
+
+            team.players = staying;
+            retired.extend(leaving.into_iter().map(|player| SquadChange { team_id: team.team.id, player }));
+
+            for player in youth_intake(team, &config.squad, next_start, &names, &mut next_id, &mut development_rng)
+            {
+                youth.push(SquadChange { team_id: team.team.id, player: player.clone() });
+                team.players.push(player);
+            }
+
+            if let Some(strength) = strengths.iter_mut().find(|strength| strength.team_id == team.team.id)
+            {
+                let rating_after: f32 = average_rating(&team.players);
+
+                if rating_before > 0.0
+                {
+                    strength.rating = (strength.rating as f32 * rating_after / rating_before).round() as u32;
+                }
+            }
+        }
+
+        seasons.push(CareerSeason { start, divisions: division_tables, playoffs: played.playoffs, moves: played.moves, retired, youth });
+    }
+
+    return Ok(Career { seed, seasons, squads, strengths, pyramid });
+}
+
+fn season_start(first: NaiveDate, season: u32) -> NaiveDate
+{
+    return first.with_year(first.year() + season as i32).unwrap_or(first);
+}
+
+fn average_rating(players: &[Player]) -> f32
+{
+    if players.is_empty()
+    {
+        return 0.0;
+    }
+
+    return players.iter().map(|player| player.rating() as f32).sum::<f32>() / players.len() as f32;
+}
+
+/// Yearly change of every attribute at `age`: young players improve, players
+/// past their peak decline, faster the older they get.
+fn age_curve(age: u32) -> i32
+{
+    match age
+    {
+        0..=20 => 4,
+        21..=23 => 2,
+        24..=27 => 0,
+        28..=30 => -1,
+        31..=33 => -3,
+        _ => -5
+    }
+}
+
+/// Age `player` by a season, developing their attributes along the age curve
+/// with some luck. Pace and stamina go first once a player is past 28.
+pub fn develop(player: &mut Player, date: NaiveDate, rng: &mut impl Rng)
+{
+    let age: u32 = player.age_on(date).unwrap_or(PEAK_AGE);
+    let curve_age: u32 = if player.position == Position::Goalkeeper { age.saturating_sub(GOALKEEPER_AGE_SHIFT) } else { age };
+    let change: i32 = age_curve(curve_age);
+    let physical_decline: i32 = if age > 28 { -1 } else { 0 };
+
+    let mut apply = |value: u8, extra: i32| -> u8 {
+        return (value as i32 + change + extra + rng.gen_range(-2..=2)).clamp(1, 99) as u8;
+    };
+
+    let attributes: &mut Attributes = &mut player.attributes;
+    attributes.finishing = apply(attributes.finishing, 0);
+    attributes.passing = apply(attributes.passing, 0);
+    attributes.tackling = apply(attributes.tackling, 0);
+    attributes.pace = apply(attributes.pace, physical_decline);
+    attributes.stamina = apply(attributes.stamina, physical_decline);
+
+    if player.position == Position::Goalkeeper
+    {
+        attributes.goalkeeping = apply(attributes.goalkeeping, 0);
+    }
+}
+
+/// Whether `player` retires before the season starting on `date`: always at
+/// 35, from 30 when their rating has dropped too low, and from 32 on with a
+/// chance that grows every year. Players of unknown age play on.
+pub fn retires(player: &Player, date: NaiveDate, rng: &mut impl Rng) -> bool
+{
+    let age: u32 = match player.age_on(date)
+    {
+        Some(age) => age,
+        None => { return false; }
+    };
+
+    if age >= RETIREMENT_AGE || (age >= 30 && player.rating() < RETIREMENT_RATING)
+    {
+        return true;
+    }
+
+    if age >= EARLIEST_RETIREMENT_AGE
+    {
+        return rng.gen_bool(0.25 * (age - EARLIEST_RETIREMENT_AGE + 1) as f64);
+    }
+
+    return false;
+}
+
+/// New youth players, 16 to 18 years old, for every position where the team
+/// has fewer players than the squad config asks for.
+pub fn youth_intake(team: &TeamWithPlayers, squad: &SquadConfig, date: NaiveDate, names: &[String], next_id: &mut u32, rng: &mut impl Rng) -> Vec<Player>
+{
+    let mut intake: Vec<Player> = Vec::new();
+
+    for position in [Position::Goalkeeper, Position::Defender, Position::Midfield, Position::Attacker]
+    {
+        let have = team.players.iter().filter(|player| player.position == position).count() as u32;
+
+        for _ in have..squad.count(&position)
+        {
+            let age_in_days: i64 = rng.gen_range(16 * 365..19 * 365);
+            let base: i32 = rng.gen_range(35..60);
+
+            intake.push(Player {
+                id: *next_id,
+                name: youth_name(names, rng),
+                dob: Some(date - Duration::days(age_in_days)),
+                height: format!("{} cm", rng.gen_range(168..196)),
+                attributes: Attributes::roll(base, &position, rng),
+                position: position.clone(),
+                club: Some(team.team.name.clone())
+            });
+
+            *next_id += 1;
+        }
+    }
+
+    return intake;
+}
+
+/// Made up name: a first name of one existing player and the surname of another.
+fn youth_name(names: &[String], rng: &mut impl Rng) -> String
+{
+    if names.is_empty()
+    {
+        return "Youth Player".to_owned();
+    }
+
+    let first: &str = names[rng.gen_range(0..names.len())].split_whitespace().next().unwrap_or("Youth");
+    let last: Vec<&str> = names[rng.gen_range(0..names.len())].split_whitespace().skip(1).collect();
+
+    if last.is_empty()
+    {
+        return first.to_owned();
+    }
+
+    return format!("{} {}", first, last.join(" "));
+}
+
+impl Career
+{
+    pub fn print(&self)
+    {
+        for season in &self.seasons
+        {
+            let champion: String = season.divisions.first()
+                .and_then(|division| division.table.rows.first())
+                .map(|row| row.team_name.clone())
+                .unwrap_or_default();
+
+            println!("{}/{:02}: champion {}, {} teams moved division, {} retired, {} youth players joined",
+                season.start.year(), (season.start.year() + 1) % 100, champion, season.moves.len(), season.retired.len(), season.youth.len());
+        }
+    }
+}
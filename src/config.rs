@@ -0,0 +1,84 @@
+use serde::{ Serialize, Deserialize };
+
+use crate::entities::Position;
+
+/// A weight per playing position.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PositionWeights
+{
+    pub attacker: f32,
+    pub midfield: f32,
+    pub defender: f32,
+    pub goalkeeper: f32
+}
+
+impl PositionWeights
+{
+    pub fn weight(&self, position: &Position) -> f32
+    {
+        match position
+        {
+            Position::Attacker => self.attacker,
+            Position::Midfield => self.midfield,
+            Position::Defender => self.defender,
+            Position::Goalkeeper => self.goalkeeper,
+            Position::NotOnTheField => 0.0
+        }
+    }
+}
+
+/// Tuning knobs for the match engine. Every field has a default, so a config
+/// file only needs to list what it changes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineConfig
+{
+    /// How likely each position is to score, multiplied by player finishing.
+    pub scorer_weights: PositionWeights,
+    /// How likely each position is to assist, multiplied by player passing.
+    pub assist_weights: PositionWeights,
+    /// Chance that a goal has an assist at all.
+    pub assist_chance: f32,
+    /// Share of a player's rating lost when fully exhausted.
+    pub fatigue_effect: f32,
+    /// Fatigue an average player shakes off per day of rest.
+    pub recovery_per_day: f32,
+    /// Days of rest between two matchdays, lower it for a congested schedule.
+    pub days_between_matchdays: u32,
+    /// Chance per minute that a team picks up a yellow card.
+    pub yellow_card_chance: f32,
+    /// Chance per minute that a team has a player sent off straight away.
+    pub red_card_chance: f32,
+    /// How likely each position is to be booked.
+    pub card_weights: PositionWeights,
+    /// Chance per minute that a team loses a fresh player to injury, tired players get injured twice as often.
+    pub injury_chance: f32,
+    /// Yellow cards over a season that earn a one match ban.
+    pub yellow_cards_for_ban: u32,
+    /// Matches a straight red card bans a player for.
+    pub red_card_ban: u32,
+    /// Chance to score a shootout penalty when taker finishing and keeper goalkeeping are even.
+    pub penalty_conversion: f32
+}
+
+impl Default for EngineConfig
+{
+    fn default() -> Self
+    {
+        EngineConfig {
+            scorer_weights: PositionWeights { attacker: 1.0, midfield: 0.45, defender: 0.12, goalkeeper: 0.0 },
+            assist_weights: PositionWeights { attacker: 0.7, midfield: 1.0, defender: 0.35, goalkeeper: 0.02 },
+            assist_chance: 0.75,
+            fatigue_effect: 0.2,
+            recovery_per_day: 0.12,
+            days_between_matchdays: 7,
+            yellow_card_chance: 0.02,
+            red_card_chance: 0.0011,
+            card_weights: PositionWeights { attacker: 0.8, midfield: 1.0, defender: 1.3, goalkeeper: 0.15 },
+            injury_chance: 0.0025,
+            yellow_cards_for_ban: 5,
+            red_card_ban: 3,
+            penalty_conversion: 0.8
+        }
+    }
+}
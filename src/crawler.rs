@@ -1,96 +1,312 @@
-use crate::entities::Player;
-use crate::entities::Position;
+pub mod cache;
+pub mod fetch;
+pub mod parse;
+pub mod robots;
+pub mod stub;
 
-use scraper::{ Html, Selector };
-use rand::Rng;
+use serde::{ Serialize, Deserialize };
+use std::path::PathBuf;
 use std::str::FromStr;
-use crate::secret;
 
-pub fn crawl() -> Vec<Player>
-{   
-    const MAX_PAGES: u32 = 11;
-    const PER_PAGE: u32 = 50;
+use crate::entities::{ Attributes, Player, Surface, Team };
+use crate::error::{ Error, Result };
+
+use rand::Rng;
+
+use self::fetch::Fetcher;
+use self::parse::{ ClubPage, ParsedClubs, ParsedPage, SkippedRow };
 
-    let mut all_players: Vec<Player> = Vec::new();
+/// How the page number goes into the URL of a page.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scheme", rename_all = "lowercase")]
+pub enum Pagination
+{
+    /// `<base_url>/2`
+    Path,
+    /// `<base_url>?<param>=2`
+    Query { param: String },
+    /// `<base_url>?<param>=50`, counting rows skipped before the page.
+    Offset { param: String }
+}
 
-    for page in 1..MAX_PAGES+1 
+impl FromStr for Pagination
+{
+    type Err = ();
+
+    /// "path", "query:<param>" or "offset:<param>".
+    fn from_str(string: &str) -> std::result::Result<Self, Self::Err>
     {
-        let offset: u32 = (page - 1) * PER_PAGE;
-        let players = crawl_page(offset, page);
+        let (scheme, param) = match string.split_once(':')
+        {
+            Some((scheme, param)) => (scheme, param.to_owned()),
+            None => (string, String::new())
+        };
 
-        for player in players 
+        match (scheme.to_lowercase().as_ref(), param.is_empty()) {
+            ("path", true) => { Ok(Pagination::Path) },
+            ("query", false) => { Ok(Pagination::Query { param }) },
+            ("offset", false) => { Ok(Pagination::Offset { param }) },
+            _ => { Err(()) }
+        }
+    }
+}
+
+/// Where and how to crawl the player list. Read from `crawl.json`, every
+/// field has a default so the file only needs what it changes, usually
+/// `base_url`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrawlConfig
+{
+    /// URL of the first page without the page number, e.g. "https://example.com/players".
+    pub base_url: String,
+    /// Last page to crawl. Without it, crawling stops at the first page
+    /// that has no players or doesn't exist.
+    pub max_pages: Option<u32>,
+    pub pagination: Pagination,
+    /// Players listed per page, for offset pagination.
+    pub per_page: u32,
+    /// Milliseconds to wait between two requests at least.
+    pub delay_ms: u64,
+    pub user_agent: String,
+    /// Milliseconds before a request is given up on.
+    pub timeout_ms: u64,
+    /// Times a failed request is tried again.
+    pub retries: u32,
+    /// Milliseconds to wait before the first retry, doubling for every next one.
+    pub backoff_ms: u64,
+    /// Skip the crawl when robots.txt doesn't allow a page.
+    pub respect_robots: bool,
+    /// Directory to keep fetched pages in, they're revalidated instead of
+    /// downloaded again on the next crawl.
+    pub cache_dir: Option<PathBuf>,
+    /// Link to the page listing the clubs, like a league table, for crawling
+    /// clubs and their squads. A path on the site or a full URL.
+    pub clubs_url: Option<String>
+}
+
+impl Default for CrawlConfig
+{
+    fn default() -> Self
+    {
+        CrawlConfig {
+            base_url: String::new(),
+            max_pages: None,
+            pagination: Pagination::Path,
+            per_page: 50,
+            delay_ms: 1000,
+            user_agent: format!("soccer-sim/{}", env!("CARGO_PKG_VERSION")),
+            timeout_ms: 30_000,
+            retries: 3,
+            backoff_ms: 500,
+            respect_robots: true,
+            cache_dir: None,
+            clubs_url: None
+        }
+    }
+}
+
+impl CrawlConfig
+{
+    /// URL of page `page`, counting from 1.
+    pub fn page_url(&self, page: u32) -> String
+    {
+        let base_url: &str = self.base_url.trim_end_matches('/');
+        let separator: char = if base_url.contains('?') { '&' } else { '?' };
+
+        match &self.pagination
         {
-            all_players.push(player);
+            Pagination::Path => format!("{}/{}", base_url, page),
+            Pagination::Query { param } => format!("{}{}{}={}", base_url, separator, param, page),
+            Pagination::Offset { param } => format!("{}{}{}={}", base_url, separator, param, (page - 1) * self.per_page)
         }
     }
 
-    println!("{}", all_players.len());
+    /// Check the config can be used to crawl over HTTP.
+    pub fn validate(&self) -> Result<()>
+    {
+        if !self.base_url.starts_with("http://") && !self.base_url.starts_with("https://")
+        {
+            return Err(Error::Validation(format!("crawl base_url '{}' isn't an http(s) URL, set it in crawl.json or with --base-url", self.base_url)));
+        }
+
+        if self.per_page == 0
+        {
+            return Err(Error::Validation("crawl per_page must be at least 1".to_owned()));
+        }
+
+        return Ok(());
+    }
+}
 
-    return all_players;
+/// The players of a crawl and the table rows that gave none, with the page
+/// each row is on.
+#[derive(Clone, Debug, Default)]
+pub struct CrawledPlayers
+{
+    pub players: Vec<Player>,
+    pub skipped: Vec<(u32, SkippedRow)>
 }
 
-fn crawl_page(id_offset: u32, i: u32) -> Vec<Player>
+/// Crawl the pages of the player list from `fetcher`, up to `config.max_pages`
+/// or until a page is missing or has no players. Players are numbered from 0
+/// in the order they're crawled.
+pub fn crawl(fetcher: &mut impl Fetcher, config: &CrawlConfig, rng: &mut impl Rng) -> Result<CrawledPlayers>
 {
-    let mut players: Vec<Player> = Vec::new();
-    let mut player_id: u32 = id_offset;
+    let mut crawled: CrawledPlayers = CrawledPlayers::default();
+    let mut page: u32 = 1;
+
+    while config.max_pages.is_none_or(|max_pages| page <= max_pages)
+    {
+        let html: String = match fetcher.fetch_page(page)?
+        {
+            Some(html) => html,
+            None => { break; }
+        };
+
+        let parsed: ParsedPage = parse::parse_players(&html, crawled.players.len() as u32)?;
+
+        crawled.skipped.extend(parsed.skipped.into_iter().map(|row| (page, row)));
+
+        if parsed.players.is_empty()
+        {
+            break;
+        }
 
-    let url: String = secret::get_vicitim().to_owned();
-    let page_url = url + "/" + i.to_string().as_ref();
+        for mut player in parsed.players
+        {
+            roll_attributes(&mut player, rng);
+            crawled.players.push(player);
+        }
 
-    let page_content: String = reqwest::blocking::
-        get(page_url).unwrap().text().unwrap();
+        page += 1;
+    }
 
-    let document = Html::parse_document(page_content.as_str());
+    return Ok(crawled);
+}
 
-    //Loop over tables....
-    let table_selector = Selector::parse("table.standard_tabelle").unwrap();
-    for table in document.select(&table_selector) 
+/// The clubs of a crawl as teams, the players of their squads and the table
+/// rows that gave neither, with the link of the page each row is on.
+#[derive(Clone, Debug, Default)]
+pub struct CrawledClubs
+{
+    pub teams: Vec<Team>,
+    pub players: Vec<Player>,
+    pub skipped: Vec<(String, SkippedRow)>
+}
+
+impl CrawledClubs
+{
+    /// Give the crawled teams the ids, and the league-since year the crawl
+    /// doesn't know, they have in `existing`, matched by name, so files keyed
+    /// by team id still fit. Clubs that aren't in `existing` are numbered
+    /// after the highest id in use.
+    pub fn keep_team_ids(&mut self, existing: &[Team])
     {
-        let mut values: Vec<String> = Vec::new();
+        let mut next_id: u32 = existing.iter().map(|team| team.id).max().unwrap_or(0) + 1;
 
-        //Loop over table rows...
-        let table_row_selector = Selector::parse("tr").unwrap();
-        for table_row in table.select(&table_row_selector)
+        for team in self.teams.iter_mut()
         {
-            //Loop over table date...
-            let table_data_selector = Selector::parse("td").unwrap();
-            for table_data in table_row.select(&table_data_selector)
+            match existing.iter().find(|known| known.name.eq_ignore_ascii_case(&team.name))
             {
-                let text = table_data.inner_html().replace("\r", "").replace("\n", "");
-
-                //Stip <a> tags if we have one...
-                if text.contains("<a href") && !text.contains("<img")
+                Some(known) =>
                 {
-                    let text_a = table_data.select(&Selector::parse("a").unwrap()).next().unwrap();
-                    let text = text_a.inner_html().replace("\r", "").replace("\n", "");
-                    values.push(text);   
-                }
-                else if !text.contains("<img")
+                    team.id = known.id;
+                    team.since = known.since;
+                },
+                None =>
                 {
-                    values.push(text);
+                    team.id = next_id;
+                    next_id += 1;
                 }
             }
+        }
+    }
+}
+
+/// Crawl the clubs on the page `config.clubs_url` links to, then the page
+/// and squad of every club. Teams are numbered from 1 in the order of the
+/// list and keep the name it gives them; players are numbered from 0 and
+/// play for the club whose squad they're in. A club whose page or squad
+/// page is missing fails the crawl.
+pub fn crawl_clubs(fetcher: &mut impl Fetcher, config: &CrawlConfig, rng: &mut impl Rng) -> Result<CrawledClubs>
+{
+    let clubs_url: &str = config.clubs_url.as_deref()
+        .ok_or_else(|| Error::Validation("crawl clubs_url isn't set, set it in crawl.json or with --clubs-url".to_owned()))?;
+
+    let html: String = fetcher.fetch_link(clubs_url)?
+        .ok_or_else(|| Error::Crawl(format!("the club list {} doesn't exist", clubs_url)))?;
+    let list: ParsedClubs = parse::parse_club_links(&html)?;
+
+    if list.clubs.is_empty()
+    {
+        return Err(Error::Crawl(format!("no clubs found on {}", clubs_url)));
+    }
 
-            let mut rng = rand::thread_rng();
+    let mut crawled: CrawledClubs = CrawledClubs::default();
+    crawled.skipped.extend(list.skipped.into_iter().map(|row| (clubs_url.to_owned(), row)));
 
-            if values.len() >= 5 
+    for (index, club) in list.clubs.into_iter().enumerate()
+    {
+        let club_html: String = fetcher.fetch_link(&club.link)?
+            .ok_or_else(|| Error::Crawl(format!("the page of {} at {} doesn't exist", club.name, club.link)))?;
+        let page: ClubPage = parse::parse_club(&club_html)?;
+
+        let team: Team = Team {
+            id: index as u32 + 1,
+            name: club.name.clone(),
+            city: page.city.unwrap_or_default(),
+            stadium: page.stadium.unwrap_or_default(),
+            surface: page.surface.map(|surface| Surface::from_str(&surface).unwrap_or_default()).unwrap_or_default(),
+            //The club pages don't say since when a club plays in the league.
+            since: 0,
+            founded: page.founded
+        };
+
+        //The squad has a page of its own or is on the club page.
+        let (squad_link, squad_html): (String, String) = match page.squad_link
+        {
+            Some(link) =>
             {
-                let player: Player = Player {
-                    id: player_id,
-                    name: values.get(0).unwrap().to_owned(),
-                    dob: values.get(2).unwrap().to_owned(),
-                    height: values.get(3).unwrap().to_owned(),
-                    position: Position::from_str(values.get(4).unwrap()).unwrap(),
-                    quality: 50 + rng.gen_range(0..50) as u8
-                };
-
-                player_id += 1;
-                players.push(player);
-            }
-            
-            values.clear();
+                let html: String = fetcher.fetch_link(&link)?
+                    .ok_or_else(|| Error::Crawl(format!("the squad of {} at {} doesn't exist", club.name, link)))?;
+                (link, html)
+            },
+            None => (club.link, club_html)
+        };
+
+        let squad: ParsedPage = parse::parse_players(&squad_html, crawled.players.len() as u32)?;
+        crawled.skipped.extend(squad.skipped.into_iter().map(|row| (squad_link.clone(), row)));
+
+        for mut player in squad.players
+        {
+            roll_attributes(&mut player, rng);
+            player.club = Some(team.name.clone());
+            crawled.players.push(player);
         }
+
+        crawled.teams.push(team);
+    }
+
+    return Ok(crawled);
+}
+
+/// The site has no ratings, so roll them.
+fn roll_attributes(player: &mut Player, rng: &mut impl Rng)
+{
+    player.attributes = Attributes::roll(50 + rng.gen_range(0..50), &player.position, rng);
+}
+
+/// FNV-1a hash of `bytes`, the same on every run and platform.
+pub(crate) fn fnv_hash(bytes: &[u8]) -> u64
+{
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in bytes
+    {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
     }
 
-    return players;
-}
\ No newline at end of file
+    return hash;
+}
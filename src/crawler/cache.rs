@@ -0,0 +1,56 @@
+use serde::{ Serialize, Deserialize };
+use std::path::{ Path, PathBuf };
+
+use crate::crawler;
+use crate::error::{ Error, Result };
+use crate::storage;
+
+/// A page as it was last fetched, with what the server told us to revalidate it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CachedPage
+{
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String
+}
+
+/// Fetched pages on disk, one JSON file per URL.
+pub struct Cache
+{
+    dir: PathBuf
+}
+
+impl Cache
+{
+    pub fn open(dir: &Path) -> Result<Cache>
+    {
+        std::fs::create_dir_all(dir).map_err(|error| Error::Io(dir.to_path_buf(), error))?;
+
+        return Ok(Cache { dir: dir.to_path_buf() });
+    }
+
+    /// The cached page for `url`. An entry that can't be read is treated as missing.
+    pub fn get(&self, url: &str) -> Option<CachedPage>
+    {
+        let path: PathBuf = self.path(url);
+
+        if !path.exists()
+        {
+            return None;
+        }
+
+        return storage::load_json::<CachedPage>(&path).ok().filter(|page| page.url == url);
+    }
+
+    pub fn put(&self, page: &CachedPage) -> Result<()>
+    {
+        return storage::save_json(&self.path(&page.url), page);
+    }
+
+    /// File of `url`, named after its FNV-1a hash so it stays the same between runs.
+    fn path(&self, url: &str) -> PathBuf
+    {
+        return self.dir.join(format!("{:016x}.json", crawler::fnv_hash(url.as_bytes())));
+    }
+}
@@ -0,0 +1,312 @@
+use chrono::{ DateTime, Utc };
+use std::path::{ Path, PathBuf };
+use std::thread;
+use std::time::{ Duration, Instant, SystemTime };
+
+use reqwest::StatusCode;
+use reqwest::header::{ HeaderMap, HeaderName, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER };
+
+use crate::crawler::CrawlConfig;
+use crate::crawler::cache::{ Cache, CachedPage };
+use crate::crawler::robots::Robots;
+use crate::error::{ Error, Result };
+
+/// Where crawled pages come from. Pages of the player list are numbered
+/// from 1, other pages are found by the links to them. A page that doesn't
+/// exist is `None`.
+pub trait Fetcher
+{
+    fn fetch_page(&mut self, page: u32) -> Result<Option<String>>;
+
+    /// Fetch the page a link on the site points to, a path like
+    /// `/teams/ajax/` or a full URL. Fetchers that only know the player
+    /// list pages can't follow links and fail.
+    fn fetch_link(&mut self, _link: &str) -> Result<Option<String>>
+    {
+        return Err(Error::Crawl("this fetcher can't follow links".to_owned()));
+    }
+}
+
+/// Fetch pages over HTTP from the URLs `config` gives them. Requests time
+/// out, failed ones are retried with a growing wait in between, and at
+/// least `config.delay_ms`, or the robots.txt crawl delay when that's
+/// longer, is left between two requests. Pages robots.txt disallows aren't
+/// fetched. With a cache directory, pages are kept on disk and revalidated
+/// with their ETag or Last-Modified date on the next crawl.
+pub struct HttpFetcher
+{
+    config: CrawlConfig,
+    client: reqwest::blocking::Client,
+    last_request: Option<Instant>,
+    robots: Option<Robots>,
+    cache: Option<Cache>
+}
+
+impl HttpFetcher
+{
+    pub fn new(config: &CrawlConfig) -> Result<HttpFetcher>
+    {
+        config.validate()?;
+
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(config.user_agent.as_str())
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()?;
+
+        let cache: Option<Cache> = match &config.cache_dir
+        {
+            Some(dir) => Some(Cache::open(dir)?),
+            None => None
+        };
+
+        return Ok(HttpFetcher { config: config.clone(), client, last_request: None, robots: None, cache });
+    }
+
+    /// Fetch `url`, `None` when the server doesn't have it.
+    pub fn fetch(&mut self, url: &str) -> Result<Option<String>>
+    {
+        let parsed = reqwest::Url::parse(url).map_err(|error| Error::Crawl(format!("invalid URL {}: {}", url, error)))?;
+
+        if self.config.respect_robots
+        {
+            let path: String = match parsed.query()
+            {
+                Some(query) => format!("{}?{}", parsed.path(), query),
+                None => parsed.path().to_owned()
+            };
+
+            if !self.robots(&parsed)?.allows(&path)
+            {
+                return Err(Error::Crawl(format!("robots.txt doesn't allow crawling {}", url)));
+            }
+        }
+
+        let cached: Option<CachedPage> = self.cache.as_ref().and_then(|cache| cache.get(url));
+        let mut headers = HeaderMap::new();
+
+        if let Some(page) = &cached
+        {
+            if let Some(value) = page.etag.as_ref().and_then(|etag| HeaderValue::from_str(etag).ok())
+            {
+                headers.insert(IF_NONE_MATCH, value);
+            }
+
+            if let Some(value) = page.last_modified.as_ref().and_then(|date| HeaderValue::from_str(date).ok())
+            {
+                headers.insert(IF_MODIFIED_SINCE, value);
+            }
+        }
+
+        let response = self.send(url, headers)?;
+        let status: StatusCode = response.status();
+
+        if status == StatusCode::NOT_MODIFIED
+        {
+            if let Some(page) = cached
+            {
+                return Ok(Some(page.body));
+            }
+        }
+
+        if status == StatusCode::NOT_FOUND
+        {
+            return Ok(None);
+        }
+
+        let response = response.error_for_status()?;
+        let header = |name: HeaderName| -> Option<String> {
+            return response.headers().get(name).and_then(|value| value.to_str().ok()).map(|value| value.to_owned());
+        };
+        let etag: Option<String> = header(ETAG);
+        let last_modified: Option<String> = header(LAST_MODIFIED);
+        let body: String = response.text()?;
+
+        if let Some(cache) = &self.cache
+        {
+            if etag.is_some() || last_modified.is_some()
+            {
+                cache.put(&CachedPage { url: url.to_owned(), etag, last_modified, body: body.clone() })?;
+            }
+        }
+
+        return Ok(Some(body));
+    }
+
+    /// The robots.txt rules of the site, fetched the first time they're
+    /// needed. As RFC 9309 has it, a site whose robots.txt is missing (any
+    /// 4xx) allows everything, and one that answers with a server error or
+    /// doesn't answer at all allows nothing.
+    fn robots(&mut self, url: &reqwest::Url) -> Result<&Robots>
+    {
+        if self.robots.is_none()
+        {
+            let robots_url: String = format!("{}/robots.txt", url.origin().ascii_serialization());
+
+            let robots: Robots = match self.send(&robots_url, HeaderMap::new())
+            {
+                Ok(response) if response.status().is_success() => Robots::parse(&response.text()?, &self.config.user_agent),
+                Ok(response) if response.status().is_client_error() => Robots::allow_all(),
+                _ => Robots::disallow_all()
+            };
+
+            self.robots = Some(robots);
+        }
+
+        return Ok(self.robots.as_ref().unwrap());
+    }
+
+    /// Send a GET request, retrying timeouts, connection failures, server
+    /// errors and 429s up to `config.retries` times. The wait before a retry
+    /// starts at `config.backoff_ms` and doubles every time, or is as long as
+    /// the Retry-After of a 429 or 503 asks for when that's longer. A server
+    /// asking for more than `MAX_RETRY_AFTER` isn't retried.
+    fn send(&mut self, url: &str, headers: HeaderMap) -> Result<reqwest::blocking::Response>
+    {
+        const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+        let mut last_error: String = String::new();
+        let mut retry_after: Option<Duration> = None;
+
+        for attempt in 0..=self.config.retries
+        {
+            if attempt > 0
+            {
+                let backoff: Duration = Duration::from_millis(self.config.backoff_ms.saturating_mul(1 << (attempt - 1).min(16)));
+                thread::sleep(backoff.max(retry_after.take().unwrap_or_default()));
+            }
+
+            self.wait_turn();
+
+            match self.client.get(url).headers(headers.clone()).send()
+            {
+                Ok(response) if response.status().is_server_error() || response.status() == StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    last_error = format!("server answered {}", response.status());
+
+                    if matches!(response.status(), StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE)
+                    {
+                        retry_after = response.headers().get(RETRY_AFTER).and_then(parse_retry_after);
+                    }
+
+                    if let Some(wait) = retry_after.filter(|wait| *wait > MAX_RETRY_AFTER)
+                    {
+                        return Err(Error::Crawl(format!("{}: {}, asking to retry after {}s", url, last_error, wait.as_secs())));
+                    }
+                },
+                Ok(response) => { return Ok(response); },
+                Err(error) if error.is_timeout() || error.is_connect() || error.is_request() =>
+                {
+                    last_error = error.to_string();
+                },
+                Err(error) => { return Err(error.into()); }
+            }
+        }
+
+        return Err(Error::Crawl(format!("{} failed after {} attempts: {}", url, self.config.retries + 1, last_error)));
+    }
+
+    /// Wait until enough time has passed since the last request.
+    fn wait_turn(&mut self)
+    {
+        let mut delay: Duration = Duration::from_millis(self.config.delay_ms);
+
+        if let Some(crawl_delay) = self.robots.as_ref().and_then(|robots| robots.crawl_delay)
+        {
+            delay = delay.max(crawl_delay);
+        }
+
+        if let Some(last_request) = self.last_request
+        {
+            thread::sleep(delay.saturating_sub(last_request.elapsed()));
+        }
+
+        self.last_request = Some(Instant::now());
+    }
+}
+
+/// The wait a Retry-After header asks for, given in seconds or as an HTTP date.
+fn parse_retry_after(value: &HeaderValue) -> Option<Duration>
+{
+    let value: &str = value.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>()
+    {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date: DateTime<Utc> = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    let now: DateTime<Utc> = DateTime::from(SystemTime::now());
+
+    return Some((date - now).to_std().unwrap_or_default());
+}
+
+impl Fetcher for HttpFetcher
+{
+    fn fetch_page(&mut self, page: u32) -> Result<Option<String>>
+    {
+        let url: String = self.config.page_url(page);
+        return self.fetch(&url);
+    }
+
+    /// Links are taken relative to `config.base_url`.
+    fn fetch_link(&mut self, link: &str) -> Result<Option<String>>
+    {
+        let url = reqwest::Url::parse(&self.config.base_url)
+            .and_then(|base_url| base_url.join(link))
+            .map_err(|error| Error::Crawl(format!("invalid link {}: {}", link, error)))?;
+
+        return self.fetch(url.as_str());
+    }
+}
+
+/// Read pages saved to a directory, page `n` from `<dir>/n.html` and the
+/// page of a link from its path, `/teams/ajax/` from `<dir>/teams/ajax.html`.
+pub struct DirFetcher
+{
+    dir: PathBuf
+}
+
+impl DirFetcher
+{
+    pub fn new(dir: &Path) -> DirFetcher
+    {
+        return DirFetcher { dir: dir.to_path_buf() };
+    }
+}
+
+impl Fetcher for DirFetcher
+{
+    fn fetch_page(&mut self, page: u32) -> Result<Option<String>>
+    {
+        return read_page(self.dir.join(format!("{}.html", page)));
+    }
+
+    fn fetch_link(&mut self, link: &str) -> Result<Option<String>>
+    {
+        //Only the path of a full URL counts, the site is always the directory.
+        let path: String = match reqwest::Url::parse(link)
+        {
+            Ok(url) => url.path().to_owned(),
+            Err(_) => link.split(['?', '#']).next().unwrap_or("").to_owned()
+        };
+
+        let name: &str = path.trim_matches('/');
+
+        if name.is_empty() || name.split('/').any(|part| part == "..")
+        {
+            return Ok(None);
+        }
+
+        return read_page(self.dir.join(format!("{}.html", name)));
+    }
+}
+
+fn read_page(path: PathBuf) -> Result<Option<String>>
+{
+    match std::fs::read_to_string(&path)
+    {
+        Ok(html) => Ok(Some(html)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(Error::Io(path, error))
+    }
+}
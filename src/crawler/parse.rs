@@ -0,0 +1,448 @@
+use scraper::{ ElementRef, Html, Selector };
+use std::fmt;
+use std::str::FromStr;
+
+use crate::entities::{ self, Attributes, Player, Position };
+use crate::error::Result;
+
+/// The columns of the player table that are read, found by their header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column
+{
+    Name,
+    Dob,
+    Height,
+    Position,
+    Club
+}
+
+impl Column
+{
+    /// The column a header cell names, in Dutch, German or English.
+    fn from_header(header: &str) -> Option<Column>
+    {
+        let header: String = header.trim().trim_end_matches(['.', ':']).to_lowercase();
+
+        match header.as_ref()
+        {
+            "speler" | "spieler" | "player" | "naam" | "name" => Some(Column::Name),
+            "geboren" | "geb" | "born" | "geburtsdatum" | "geboortedatum" | "date of birth" => Some(Column::Dob),
+            "lengte" | "größe" | "grösse" | "groesse" | "height" => Some(Column::Height),
+            "positie" | "position" | "pos" => Some(Column::Position),
+            "club" | "team" | "ploeg" | "verein" | "mannschaft" => Some(Column::Club),
+            _ => None
+        }
+    }
+}
+
+/// Why a table row gave no player.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SkipReason
+{
+    /// The table has no header row naming the column the rows are about.
+    NoHeader,
+    /// The row ends before the column.
+    MissingCell(Column),
+    /// One cell spans the column and the one given, like a note across the table.
+    MergedCells(Column, Column),
+    EmptyName,
+    /// The position isn't one of the codes a player can play at.
+    UnknownPosition(String),
+    /// The date of birth is neither a date nor left unknown, like "???".
+    InvalidDate(String),
+    /// The club cell doesn't link to the club's page.
+    NoLink
+}
+
+impl fmt::Display for SkipReason
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            SkipReason::NoHeader => write!(f, "the table has no header naming the columns to read"),
+            SkipReason::MissingCell(column) => write!(f, "no {:?} cell", column),
+            SkipReason::MergedCells(first, second) => write!(f, "one cell spans {:?} and {:?}", first, second),
+            SkipReason::EmptyName => write!(f, "the name is empty"),
+            SkipReason::UnknownPosition(position) => write!(f, "unknown position \"{}\"", position),
+            SkipReason::InvalidDate(date) => write!(f, "unreadable date of birth \"{}\"", date),
+            SkipReason::NoLink => write!(f, "no link to the club page")
+        }
+    }
+}
+
+/// A table row that gave no player or club. Tables and rows count from 1, the header
+/// row included.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedRow
+{
+    pub table: usize,
+    pub row: usize,
+    pub reason: SkipReason,
+    /// Text of the row's cells, to find it back on the page.
+    pub text: String
+}
+
+impl fmt::Display for SkippedRow
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "table {}, row {}: {} ({})", self.table, self.row, self.reason, self.text)
+    }
+}
+
+/// The players of a page and the rows that didn't give one.
+#[derive(Clone, Debug, Default)]
+pub struct ParsedPage
+{
+    pub players: Vec<Player>,
+    pub skipped: Vec<SkippedRow>
+}
+
+/// Players in the `table.standard_tabelle` tables of a player list or squad
+/// page, numbered from `first_id`. Columns are found by the header row, so
+/// their order doesn't matter and columns that aren't read, like the club
+/// or a flag, are passed over. Of the players the name, date of birth, height and
+/// position are read; the site has no ratings, so attributes are left at
+/// zero. Rows that give no player, an unknown position or a date of birth
+/// that can't be read are listed with the reason.
+pub fn parse_players(html: &str, first_id: u32) -> Result<ParsedPage>
+{
+    let mut page: ParsedPage = ParsedPage::default();
+    let mut player_id: u32 = first_id;
+
+    let document = Html::parse_document(html);
+
+    for row in read_tables(&document, Column::Name)
+    {
+        let row: TableRow = match row
+        {
+            Ok(row) => row,
+            Err(skipped) =>
+            {
+                page.skipped.push(skipped);
+                continue;
+            }
+        };
+
+        let name: String = row.text(Column::Name);
+
+        if name.is_empty()
+        {
+            page.skipped.push(row.skip(SkipReason::EmptyName));
+            continue;
+        }
+
+        let position: Position = match Position::from_str(&row.text(Column::Position))
+        {
+            Ok(Position::NotOnTheField) | Err(_) =>
+            {
+                page.skipped.push(row.skip(SkipReason::UnknownPosition(row.text(Column::Position))));
+                continue;
+            },
+            Ok(position) => position
+        };
+
+        let dob_text: String = row.text(Column::Dob);
+        let dob = entities::parse_dob(&dob_text);
+
+        //Question marks or dashes, or nothing at all, mean the date isn't known.
+        if dob.is_none() && !dob_text.chars().all(|c| c == '?' || c == '-')
+        {
+            page.skipped.push(row.skip(SkipReason::InvalidDate(dob_text)));
+            continue;
+        }
+
+        page.players.push(Player {
+            id: player_id,
+            name,
+            dob,
+            height: row.text(Column::Height),
+            attributes: Attributes::default(),
+            position,
+            club: None
+        });
+
+        player_id += 1;
+    }
+
+    return Ok(page);
+}
+
+/// A club on the club list, with the link to its page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClubLink
+{
+    pub name: String,
+    pub link: String
+}
+
+/// The clubs of a club list page and the rows that didn't give one.
+#[derive(Clone, Debug, Default)]
+pub struct ParsedClubs
+{
+    pub clubs: Vec<ClubLink>,
+    pub skipped: Vec<SkippedRow>
+}
+
+/// Clubs in the `table.standard_tabelle` tables of a club list page, like a
+/// league table, read from the links under the club column.
+pub fn parse_club_links(html: &str) -> Result<ParsedClubs>
+{
+    let mut parsed: ParsedClubs = ParsedClubs::default();
+
+    let document = Html::parse_document(html);
+    let link_selector = Selector::parse("a[href]").unwrap();
+
+    for row in read_tables(&document, Column::Club)
+    {
+        let row: TableRow = match row
+        {
+            Ok(row) => row,
+            Err(skipped) =>
+            {
+                parsed.skipped.push(skipped);
+                continue;
+            }
+        };
+
+        let link: Option<ElementRef> = row.cell(Column::Club).and_then(|cell| cell.select(&link_selector).next());
+
+        match link
+        {
+            Some(link) if !text_of(link).is_empty() =>
+            {
+                let href: &str = link.value().attr("href").unwrap_or_default();
+                parsed.clubs.push(ClubLink { name: text_of(link), link: href.to_owned() });
+            },
+            Some(_) => parsed.skipped.push(row.skip(SkipReason::EmptyName)),
+            None => parsed.skipped.push(row.skip(SkipReason::NoLink))
+        }
+    }
+
+    return Ok(parsed);
+}
+
+/// What a club page tells about the club. Fields the page doesn't have are
+/// left empty.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClubPage
+{
+    pub city: Option<String>,
+    pub stadium: Option<String>,
+    pub surface: Option<String>,
+    pub founded: Option<u32>,
+    /// Link to the page with the squad, when it isn't on the club page itself.
+    pub squad_link: Option<String>
+}
+
+/// Read a club page. The details are rows of a label ending in ':' and a
+/// value, like `<td>Opgericht:</td><td>18.03.1900</td>`, in Dutch, German
+/// or English. The squad is behind a link named "Selectie", "Kader" or
+/// "Squad".
+pub fn parse_club(html: &str) -> Result<ClubPage>
+{
+    let mut club: ClubPage = ClubPage::default();
+
+    let document = Html::parse_document(html);
+    let row_selector = Selector::parse("tr").unwrap();
+    let cell_selector = Selector::parse("th, td").unwrap();
+    let link_selector = Selector::parse("a[href]").unwrap();
+
+    for row in document.select(&row_selector)
+    {
+        let cells: Vec<String> = row.select(&cell_selector).map(text_of).collect();
+
+        if cells.len() < 2 || !cells[0].ends_with(':') || cells[1].is_empty()
+        {
+            continue;
+        }
+
+        let value: String = cells[1].clone();
+
+        match cells[0].trim_end_matches(':').trim().to_lowercase().as_ref()
+        {
+            "stad" | "plaats" | "stadt" | "ort" | "city" => club.city = Some(value),
+            "stadion" | "stadium" => club.stadium = Some(value),
+            "veld" | "ondergrond" | "rasen" | "spielfläche" | "surface" | "pitch" => club.surface = Some(value),
+            "opgericht" | "gegründet" | "founded" => club.founded = year_of(&value),
+            _ => {}
+        }
+    }
+
+    club.squad_link = document.select(&link_selector)
+        .find(|link| matches!(text_of(*link).to_lowercase().as_ref(), "selectie" | "kader" | "squad"))
+        .and_then(|link| link.value().attr("href"))
+        .map(|href| href.to_owned());
+
+    return Ok(club);
+}
+
+/// The last four digit number in `text`, the year of a date like "18.03.1900".
+fn year_of(text: &str) -> Option<u32>
+{
+    return text.split(|character: char| !character.is_ascii_digit())
+        .rfind(|part| part.len() == 4)
+        .and_then(|year| year.parse::<u32>().ok());
+}
+
+/// A table row that isn't a header, with the cell under every column read.
+struct TableRow<'a>
+{
+    table: usize,
+    row: usize,
+    cells: Vec<(Column, ElementRef<'a>)>,
+    /// Text of all cells of the row, to find it back on the page.
+    text: String
+}
+
+impl<'a> TableRow<'a>
+{
+    fn cell(&self, column: Column) -> Option<ElementRef<'a>>
+    {
+        return self.cells.iter().find(|(read, _)| *read == column).map(|(_, cell)| *cell);
+    }
+
+    /// Text of the cell under `column`, empty when the header doesn't name it.
+    fn text(&self, column: Column) -> String
+    {
+        return self.cell(column).map(text_of).unwrap_or_default();
+    }
+
+    fn skip(&self, reason: SkipReason) -> SkippedRow
+    {
+        return SkippedRow { table: self.table, row: self.row, reason, text: self.text.clone() };
+    }
+}
+
+/// Read the rows of the `table.standard_tabelle` tables of `document` by
+/// the columns their header row names. Only a header naming `key` counts,
+/// other header rows, like a title above the table, are passed over. Rows
+/// that can't be read are skipped with the reason.
+fn read_tables(document: &Html, key: Column) -> Vec<std::result::Result<TableRow<'_>, SkippedRow>>
+{
+    let mut rows: Vec<std::result::Result<TableRow, SkippedRow>> = Vec::new();
+
+    let table_selector = Selector::parse("table.standard_tabelle").unwrap();
+    let table_row_selector = Selector::parse("tr").unwrap();
+    let cell_selector = Selector::parse("th, td").unwrap();
+
+    //Loop over tables....
+    for (table_index, table) in document.select(&table_selector).enumerate()
+    {
+        //The column read from every slot, slots being the columns after colspans.
+        let mut columns: Option<Vec<Option<Column>>> = None;
+
+        //Loop over table rows...
+        for (row_index, table_row) in table.select(&table_row_selector).enumerate()
+        {
+            let cells: Vec<ElementRef> = table_row.select(&cell_selector).collect();
+
+            if cells.is_empty()
+            {
+                continue;
+            }
+
+            let cell_slots: Vec<usize> = slots(&cells);
+
+            if cells.iter().all(|cell| cell.value().name() == "th")
+            {
+                let header: Vec<Option<Column>> = cell_slots.iter().map(|cell| Column::from_header(&text_of(cells[*cell]))).collect();
+
+                if columns.is_none() && header.contains(&Some(key))
+                {
+                    columns = Some(header);
+                }
+
+                continue;
+            }
+
+            let texts: Vec<String> = cells.iter().map(|cell| text_of(*cell)).collect();
+            let text: String = texts.join(" | ");
+            let read = match &columns
+            {
+                Some(columns) => read_row(columns, &cell_slots, &texts),
+                None => Err(SkipReason::NoHeader)
+            };
+
+            rows.push(match read
+            {
+                Ok(read) => Ok(TableRow {
+                    table: table_index + 1,
+                    row: row_index + 1,
+                    cells: read.into_iter().map(|(column, cell)| (column, cells[cell])).collect(),
+                    text
+                }),
+                Err(reason) => Err(SkippedRow { table: table_index + 1, row: row_index + 1, reason, text })
+            });
+        }
+    }
+
+    return rows;
+}
+
+/// The cell under every column of `columns`, `cell_slots` giving the cell
+/// in every slot of the row and `texts` the text of every cell. Under a
+/// header spanning several cells, like a crest and a club name, the first
+/// cell with text counts.
+fn read_row(columns: &[Option<Column>], cell_slots: &[usize], texts: &[String]) -> std::result::Result<Vec<(Column, usize)>, SkipReason>
+{
+    let mut read: Vec<(Column, usize)> = Vec::new();
+
+    for (first_slot, column) in columns.iter().enumerate()
+    {
+        let column: Column = match column
+        {
+            Some(column) if !columns[..first_slot].contains(&Some(*column)) => *column,
+            _ => continue
+        };
+
+        let under: Vec<usize> = columns.iter()
+            .enumerate()
+            .filter(|(_, slot_column)| **slot_column == Some(column))
+            .filter_map(|(slot, _)| cell_slots.get(slot).copied())
+            .collect();
+
+        let cell: usize = match under.iter().find(|cell| !texts[**cell].is_empty()).or(under.first())
+        {
+            Some(cell) => *cell,
+            None => { return Err(SkipReason::MissingCell(column)); }
+        };
+
+        if let Some((other, _)) = read.iter().find(|(_, read_cell)| *read_cell == cell)
+        {
+            return Err(SkipReason::MergedCells(*other, column));
+        }
+
+        read.push((column, cell));
+    }
+
+    return Ok(read);
+}
+
+/// The cell in every slot of a row, a cell with a colspan filling several.
+fn slots(cells: &[ElementRef]) -> Vec<usize>
+{
+    let mut slots: Vec<usize> = Vec::new();
+
+    for (index, cell) in cells.iter().enumerate()
+    {
+        let span: usize = cell.value().attr("colspan")
+            .and_then(|span| span.trim().parse::<usize>().ok())
+            .unwrap_or(1)
+            .clamp(1, 100);
+
+        slots.extend(std::iter::repeat_n(index, span));
+    }
+
+    return slots;
+}
+
+/// The text of a cell with runs of whitespace, line breaks and non-breaking
+/// spaces included, made a single space. The HTML parser already decoded
+/// entities like `&amp;` and `&eacute;` in the text.
+fn text_of(cell: ElementRef) -> String
+{
+    let text: String = cell.text().collect();
+    return text.split_whitespace().collect::<Vec<&str>>().join(" ");
+}
@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+/// The rules of a robots.txt that apply to one user agent.
+#[derive(Clone, Debug, Default)]
+pub struct Robots
+{
+    /// (allowed, path pattern) for every Allow and Disallow line.
+    rules: Vec<(bool, String)>,
+    pub crawl_delay: Option<Duration>
+}
+
+impl Robots
+{
+    /// Rules for a site without a robots.txt: everything is allowed.
+    pub fn allow_all() -> Robots
+    {
+        return Robots::default();
+    }
+
+    /// Rules for a site whose robots.txt can't be read because of a server
+    /// error or no answer: nothing may be crawled.
+    pub fn disallow_all() -> Robots
+    {
+        return Robots { rules: vec![(false, "/".to_owned())], crawl_delay: None };
+    }
+
+    /// Read the group of `text` for `user_agent`, or the `*` group when no
+    /// group names it. A group names the agent when its product token, the
+    /// part before the '/', contains the group's user-agent value.
+    pub fn parse(text: &str, user_agent: &str) -> Robots
+    {
+        let product: String = user_agent.split('/').next().unwrap_or("").trim().to_lowercase();
+
+        let mut groups: Vec<(Vec<String>, Robots)> = Vec::new();
+        let mut in_agents: bool = false;
+
+        for line in text.lines()
+        {
+            let line: &str = line.split('#').next().unwrap_or("").trim();
+
+            let (field, value) = match line.split_once(':')
+            {
+                Some((field, value)) => (field.trim().to_lowercase(), value.trim()),
+                None => continue
+            };
+
+            if field == "user-agent"
+            {
+                //Several user-agent lines in a row share one group.
+                if !in_agents
+                {
+                    groups.push((Vec::new(), Robots::default()));
+                }
+
+                in_agents = true;
+                groups.last_mut().unwrap().0.push(value.to_lowercase());
+                continue;
+            }
+
+            in_agents = false;
+
+            let robots: &mut Robots = match groups.last_mut()
+            {
+                Some((_, robots)) => robots,
+                None => continue
+            };
+
+            match field.as_ref()
+            {
+                //An empty disallow allows everything, same as no rule.
+                "disallow" if !value.is_empty() => robots.rules.push((false, value.to_owned())),
+                "allow" if !value.is_empty() => robots.rules.push((true, value.to_owned())),
+                "crawl-delay" => robots.crawl_delay = value.parse::<f64>().ok().filter(|delay| *delay >= 0.0).map(Duration::from_secs_f64),
+                _ => {}
+            }
+        }
+
+        let named = groups.iter().find(|(agents, _)| agents.iter().any(|agent| agent != "*" && !product.is_empty() && product.contains(agent.as_str())));
+        let any = groups.iter().find(|(agents, _)| agents.iter().any(|agent| agent == "*"));
+
+        return named.or(any).map(|(_, robots)| robots.clone()).unwrap_or_default();
+    }
+
+    /// Whether `path`, with its query, may be fetched. The longest matching
+    /// rule decides, Allow wins a tie.
+    pub fn allows(&self, path: &str) -> bool
+    {
+        let best = self.rules.iter()
+            .filter(|(_, pattern)| matches(pattern, path))
+            .max_by_key(|(allowed, pattern)| (pattern.len(), *allowed));
+
+        return best.is_none_or(|(allowed, _)| *allowed);
+    }
+}
+
+/// Match a robots.txt path pattern against the start of `path`. '*' matches
+/// any run of characters and a trailing '$' anchors the pattern at the end.
+fn matches(pattern: &str, path: &str) -> bool
+{
+    let (pattern, anchored) = match pattern.strip_suffix('$')
+    {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false)
+    };
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let mut rest: &str = match path.strip_prefix(parts[0])
+    {
+        Some(rest) => rest,
+        None => { return false; }
+    };
+
+    for (index, part) in parts.iter().enumerate().skip(1)
+    {
+        let is_last: bool = index == parts.len() - 1;
+
+        //The last part of an anchored pattern has to match at the very end.
+        let found: Option<usize> = if is_last && anchored { rest.rfind(part) } else { rest.find(part) };
+
+        match found
+        {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => { return false; }
+        }
+    }
+
+    return !anchored || rest.is_empty();
+}
@@ -0,0 +1,204 @@
+use std::io::{ BufRead, BufReader, Write };
+use std::net::{ SocketAddr, TcpListener, TcpStream };
+use std::path::{ Path, PathBuf };
+use std::sync::{ Arc, Mutex };
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::thread::{ self, JoinHandle };
+use std::time::Duration;
+
+use crate::crawler;
+use crate::error::{ Error, Result };
+
+/// A request the stub server answered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StubRequest
+{
+    pub path: String,
+    pub status: u16,
+    pub user_agent: Option<String>
+}
+
+#[derive(Default)]
+struct StubState
+{
+    failures_left: u32,
+    retry_after: Option<String>,
+    delay: Duration,
+    requests: Vec<StubRequest>
+}
+
+/// A small local HTTP server that serves saved pages from a directory, so
+/// the HTTP fetcher can be run without touching the real site. `GET /name`
+/// and `GET /name/` answer with `<dir>/name`, or `<dir>/name.html`, and
+/// 404 otherwise.
+/// Pages carry an ETag and a matching If-None-Match gets a 304. Failures
+/// and slow answers can be switched on to test retries and timeouts.
+/// Stops when dropped.
+pub struct StubServer
+{
+    address: SocketAddr,
+    state: Arc<Mutex<StubState>>,
+    stopping: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>
+}
+
+impl StubServer
+{
+    /// Serve `dir` on a free port of 127.0.0.1.
+    pub fn start(dir: &Path) -> Result<StubServer>
+    {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .map_err(|error| Error::Crawl(format!("can't start the stub server: {}", error)))?;
+        let address: SocketAddr = listener.local_addr()
+            .map_err(|error| Error::Crawl(format!("can't start the stub server: {}", error)))?;
+
+        let state: Arc<Mutex<StubState>> = Arc::new(Mutex::new(StubState::default()));
+        let stopping = Arc::new(AtomicBool::new(false));
+        let server_state = Arc::clone(&state);
+        let server_stopping = Arc::clone(&stopping);
+        let dir: PathBuf = dir.to_path_buf();
+
+        let handle = thread::spawn(move || {
+            for stream in listener.incoming()
+            {
+                if server_stopping.load(Ordering::SeqCst)
+                {
+                    break;
+                }
+
+                if let Ok(stream) = stream
+                {
+                    //A client hanging up early isn't the server's problem.
+                    let _ = serve(stream, &dir, &server_state);
+                }
+            }
+        });
+
+        return Ok(StubServer { address, state, stopping, handle: Some(handle) });
+    }
+
+    pub fn url(&self) -> String
+    {
+        return format!("http://{}", self.address);
+    }
+
+    /// Answer the next `count` requests with 503 Service Unavailable.
+    pub fn fail_next(&self, count: u32)
+    {
+        self.state.lock().unwrap().failures_left = count;
+    }
+
+    /// Send this Retry-After header along with the 503s of `fail_next`.
+    pub fn set_retry_after(&self, value: &str)
+    {
+        self.state.lock().unwrap().retry_after = Some(value.to_owned());
+    }
+
+    /// Wait this long before answering every request.
+    pub fn set_delay(&self, delay: Duration)
+    {
+        self.state.lock().unwrap().delay = delay;
+    }
+
+    /// Every request answered so far, oldest first.
+    pub fn requests(&self) -> Vec<StubRequest>
+    {
+        return self.state.lock().unwrap().requests.clone();
+    }
+}
+
+impl Drop for StubServer
+{
+    fn drop(&mut self)
+    {
+        self.stopping.store(true, Ordering::SeqCst);
+
+        //Wake the server up from waiting for the next connection.
+        let _ = TcpStream::connect(self.address);
+
+        if let Some(handle) = self.handle.take()
+        {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn serve(stream: TcpStream, dir: &Path, state: &Mutex<StubState>) -> std::io::Result<()>
+{
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut if_none_match: Option<String> = None;
+    let mut user_agent: Option<String> = None;
+    let mut header = String::new();
+
+    while reader.read_line(&mut header)? > 2
+    {
+        if let Some((name, value)) = header.split_once(':')
+        {
+            match name.trim().to_lowercase().as_ref()
+            {
+                "if-none-match" => if_none_match = Some(value.trim().to_owned()),
+                "user-agent" => user_agent = Some(value.trim().to_owned()),
+                _ => {}
+            }
+        }
+
+        header.clear();
+    }
+
+    let path: String = request_line.split_whitespace().nth(1).unwrap_or("/").to_owned();
+    let name: &str = path.split('?').next().unwrap_or("").trim_matches('/');
+
+    let (delay, failing, retry_after) = {
+        let mut state = state.lock().unwrap();
+        let failing: bool = state.failures_left > 0;
+        state.failures_left = state.failures_left.saturating_sub(1);
+        (state.delay, failing, state.retry_after.clone())
+    };
+
+    thread::sleep(delay);
+
+    let body: Option<Vec<u8>> = if name.is_empty() || name.contains("..")
+    {
+        None
+    }
+    else
+    {
+        std::fs::read(dir.join(name)).or_else(|_| std::fs::read(dir.join(format!("{}.html", name)))).ok()
+    };
+
+    let (status, response): (u16, Vec<u8>) = match body
+    {
+        _ if failing =>
+        {
+            let retry_after: String = retry_after.map(|value| format!("Retry-After: {}\r\n", value)).unwrap_or_default();
+            (503, format!("HTTP/1.1 503 Service Unavailable\r\n{}Content-Length: 0\r\nConnection: close\r\n\r\n", retry_after).into_bytes())
+        },
+        Some(body) =>
+        {
+            let etag: String = format!("\"{:x}\"", crawler::fnv_hash(&body));
+
+            if if_none_match.as_deref() == Some(etag.as_str())
+            {
+                (304, format!("HTTP/1.1 304 Not Modified\r\nETag: {}\r\nConnection: close\r\n\r\n", etag).into_bytes())
+            }
+            else
+            {
+                let mut response: Vec<u8> = format!("HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nETag: {}\r\nConnection: close\r\n\r\n", body.len(), etag).into_bytes();
+                response.extend(body);
+                (200, response)
+            }
+        },
+        None => (404, b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec())
+    };
+
+    //Log before answering, so the client sees the request logged once it has the answer.
+    state.lock().unwrap().requests.push(StubRequest { path, status, user_agent });
+
+    let mut stream = stream;
+    stream.write_all(&response)?;
+
+    return stream.flush();
+}
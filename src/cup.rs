@@ -0,0 +1,276 @@
+use std::str::FromStr;
+use rand::seq::SliceRandom;
+use serde::{ Serialize, Deserialize };
+
+use crate::config::EngineConfig;
+use crate::entities::{ Match, TeamStrength, TeamWithPlayers };
+use crate::error::{ Error, Result };
+use crate::{ rng, simulation };
+
+/// Seed stream of the random draws, the matches use streams 0, 1, ...
+const DRAW_STREAM: u64 = u64::MAX;
+
+/// How ties are drawn.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Draw
+{
+    /// Teams are ranked by strength and the best meets the worst left in
+    /// every round. The weaker team plays at home first.
+    Seeded,
+    /// Every round is drawn from a hat, the first team drawn plays at home first.
+    Random
+}
+
+impl FromStr for Draw
+{
+    type Err = ();
+
+    fn from_str(string: &str) -> std::result::Result<Self, Self::Err>
+    {
+        match string.to_lowercase().as_ref() {
+            "seeded" => { Ok(Draw::Seeded) },
+            "random" => { Ok(Draw::Random) },
+            _ => { Err(()) }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CupConfig
+{
+    pub draw: Draw,
+    /// Play every round but the final over two legs.
+    pub two_legs: bool,
+    pub engine: EngineConfig
+}
+
+impl Default for CupConfig
+{
+    fn default() -> Self
+    {
+        CupConfig { draw: Draw::Seeded, two_legs: false, engine: EngineConfig::default() }
+    }
+}
+
+/// How the winner of a tie was found.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Decision
+{
+    Regular,
+    ExtraTime,
+    Penalties,
+    /// The last leg was abandoned and awarded, see `play_tie` for how that
+    /// counts towards the aggregate.
+    Forfeit
+}
+
+/// A cup tie, `home` plays at home in the first or only leg. Level ties go
+/// to extra time and penalties at the end of the last leg.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CupTie
+{
+    pub home: u32,
+    pub away: u32,
+    pub legs: Vec<Match>,
+    pub winner: u32,
+    pub decided_by: Decision
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CupRound
+{
+    pub name: String,
+    pub ties: Vec<CupTie>,
+    /// Teams that go through to the next round without playing.
+    pub byes: Vec<u32>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Cup
+{
+    pub seed: u64,
+    pub rounds: Vec<CupRound>,
+    pub winner: u32
+}
+
+/// Play a knockout cup between all `teams`. When the number of teams isn't
+/// a power of two, the first round gives byes to the top seeds, or to the
+/// first teams drawn, so that a power of two is left for the second round.
+pub fn play_cup(teams: &[TeamWithPlayers], strengths: &[TeamStrength], config: &CupConfig, seed: u64) -> Result<Cup>
+{
+    if teams.len() < 2
+    {
+        return Err(Error::Validation(format!("a cup needs at least 2 teams, got {}", teams.len())));
+    }
+
+    let mut draw_rng = rng::from_seed(rng::derive_seed(seed, DRAW_STREAM));
+    let mut remaining: Vec<u32> = teams.iter().map(|team| team.team.id).collect();
+
+    if config.draw == Draw::Seeded
+    {
+        //Ties in strength keep the order of the teams file.
+        let rating = |team_id: &u32| -> f32 {
+            return TeamStrength::find(strengths, *team_id).map(|strength| strength.effective_rating()).unwrap_or(0.0);
+        };
+
+        remaining.sort_by(|a, b| rating(b).partial_cmp(&rating(a)).unwrap_or(std::cmp::Ordering::Equal));
+    }
+
+    let mut rounds: Vec<CupRound> = Vec::new();
+    let mut leg_index: u64 = 0;
+
+    while remaining.len() > 1
+    {
+        if config.draw == Draw::Random
+        {
+            remaining.shuffle(&mut draw_rng);
+        }
+
+        let round_number: u32 = rounds.len() as u32 + 1;
+        let name: String = round_name(remaining.len(), round_number);
+        let is_final: bool = remaining.len() == 2;
+        let legs: u32 = if config.two_legs && !is_final { 2 } else { 1 };
+
+        //Everyone above the last power of two has to play one more round.
+        let next_round: usize = if remaining.len().is_power_of_two() { remaining.len() / 2 } else { remaining.len().next_power_of_two() / 2 };
+        let playing: usize = 2 * (remaining.len() - next_round);
+        let byes: Vec<u32> = remaining[..remaining.len() - playing].to_vec();
+        let drawn: &[u32] = &remaining[remaining.len() - playing..];
+
+        let mut ties: Vec<CupTie> = Vec::new();
+
+        for pair in 0..drawn.len() / 2
+        {
+            let (home, away) = match config.draw
+            {
+                Draw::Seeded => (drawn[drawn.len() - 1 - pair], drawn[pair]),
+                Draw::Random => (drawn[2 * pair], drawn[2 * pair + 1])
+            };
+
+            ties.push(play_tie(teams, strengths, &config.engine, home, away, legs, round_number, seed, &mut leg_index)?);
+        }
+
+        //Winners keep the seeding order they came in with.
+        remaining.retain(|team_id| byes.contains(team_id) || ties.iter().any(|tie| tie.winner == *team_id));
+        rounds.push(CupRound { name, ties, byes });
+    }
+
+    return Ok(Cup { seed, rounds, winner: remaining[0] });
+}
+
+/// Play the legs of a tie and find its winner on aggregate. A forfeited leg
+/// counts with its awarded score, in the last leg as in any other, so a team
+/// far enough ahead after the first leg can still go through. When the
+/// awarded score leaves the tie level, the team that forfeited goes out.
+#[allow(clippy::too_many_arguments)]
+fn play_tie(teams: &[TeamWithPlayers], strengths: &[TeamStrength], engine: &EngineConfig, home: u32, away: u32, legs: u32, round: u32, seed: u64, leg_index: &mut u64) -> Result<CupTie>
+{
+    let mut played: Vec<Match> = Vec::new();
+
+    for leg in 0..legs
+    {
+        let (team_home, team_out) = if leg % 2 == 0 { (home, away) } else { (away, home) };
+        let leg_seed: u64 = rng::derive_seed(seed, *leg_index);
+        *leg_index += 1;
+
+        let mut game: Match = if leg + 1 == legs
+        {
+            let earlier: (u32, u32) = (total_goals(&played, team_home), total_goals(&played, team_out));
+            simulation::play_knockout_match(teams, strengths, engine, team_home, team_out, earlier, leg_seed)?
+        }
+        else
+        {
+            simulation::play_match(teams, strengths, engine, team_home, team_out, leg_seed)?
+        };
+        game.matchday = round;
+
+        played.push(game);
+    }
+
+    let last: &Match = played.last().unwrap();
+    let decided_by: Decision = if last.forfeited_by.is_some() { Decision::Forfeit } else if last.shootout.is_some() { Decision::Penalties } else if last.extra_time { Decision::ExtraTime } else { Decision::Regular };
+    let (home_goals, away_goals) = (total_goals(&played, home), total_goals(&played, away));
+    let winner: u32 = match (&last.shootout, last.forfeited_by)
+    {
+        (Some(_), None) => last.winner().unwrap(),
+        _ if home_goals > away_goals => home,
+        _ if away_goals > home_goals => away,
+        (_, Some(team_id)) => if team_id == home { away } else { home },
+        (None, None) => away
+    };
+
+    return Ok(CupTie { home, away, legs: played, winner, decided_by });
+}
+
+fn total_goals(legs: &[Match], team_id: u32) -> u32
+{
+    return legs.iter().map(|leg| {
+        let (home_goals, out_goals) = leg.score();
+        if leg.team_home == team_id { home_goals } else if leg.team_out == team_id { out_goals } else { 0 }
+    }).sum();
+}
+
+fn round_name(teams: usize, round: u32) -> String
+{
+    match teams
+    {
+        2 => "Final".to_owned(),
+        4 => "Semi-finals".to_owned(),
+        8 => "Quarter-finals".to_owned(),
+        _ if teams.is_power_of_two() => format!("Round of {}", teams),
+        _ => format!("Round {}", round)
+    }
+}
+
+impl Cup
+{
+    /// Every match of the cup, in the order they were played.
+    pub fn matches(&self) -> Vec<Match>
+    {
+        return self.rounds.iter()
+            .flat_map(|round| round.ties.iter())
+            .flat_map(|tie| tie.legs.iter().cloned())
+            .collect();
+    }
+
+    pub fn print(&self, teams: &[TeamWithPlayers])
+    {
+        let name = |team_id: u32| -> String {
+            return teams.iter().find(|team| team.team.id == team_id).map(|team| team.team.name.clone()).unwrap_or_default();
+        };
+
+        for round in &self.rounds
+        {
+            println!("{}", round.name);
+
+            for tie in &round.ties
+            {
+                let home_goals: u32 = total_goals(&tie.legs, tie.home);
+                let away_goals: u32 = total_goals(&tie.legs, tie.away);
+                let aggregate: &str = if tie.legs.len() > 1 { " (agg.)" } else { "" };
+
+                let last: &Match = tie.legs.last().unwrap();
+
+                let decided: String = match (tie.decided_by, &last.shootout)
+                {
+                    (Decision::Penalties, Some(shootout)) if last.team_home == tie.home => format!(", {}-{} on penalties", shootout.home_goals, shootout.out_goals),
+                    (Decision::Penalties, Some(shootout)) => format!(", {}-{} on penalties", shootout.out_goals, shootout.home_goals),
+                    (Decision::ExtraTime, _) => " a.e.t.".to_owned(),
+                    (Decision::Forfeit, _) => " (awarded)".to_owned(),
+                    _ => String::new()
+                };
+
+                println!("  {} - {} {}-{}{}{}, {} through", name(tie.home), name(tie.away), home_goals, away_goals, aggregate, decided, name(tie.winner));
+            }
+
+            for team_id in &round.byes
+            {
+                println!("  {} (bye)", name(*team_id));
+            }
+
+            println!();
+        }
+
+        println!("Winner: {}", name(self.winner));
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::HashSet;
+use serde::{ Serialize, Deserialize };
+
+use crate::config::EngineConfig;
+use crate::entities::{ Match, Team, TeamStrength, TeamWithPlayers };
+use crate::error::{ Error, Result };
+use crate::table::{ LeagueTable, Tiebreaker };
+use crate::{ rng, simulation };
+
+/// Seed stream of the playoffs below division 0, the ones further down
+/// count down from it. Divisions themselves use streams 0, 1, ...
+const PLAYOFF_STREAM: u64 = u64::MAX - 1;
+
+/// One tier of the pyramid.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Division
+{
+    pub name: String,
+    pub team_ids: Vec<u32>
+}
+
+/// A bracket between the bottom of one division and the top of the one
+/// below. Teams are seeded upper division first, then by place, and meet
+/// the seed furthest from them over two legs until `spots` teams are left.
+/// Those play in the upper division next season.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Playoff
+{
+    /// Places (1 is top) of the upper division that play in the playoff.
+    pub upper_places: Vec<u32>,
+    /// Places of the lower division that play in the playoff.
+    pub lower_places: Vec<u32>,
+    pub spots: u32
+}
+
+/// Promotion and relegation between a division and the one below it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Movement
+{
+    /// Bottom teams of the upper division that go down.
+    pub relegated: u32,
+    /// Top teams of the lower division that go up.
+    pub promoted: u32,
+    #[serde(default)]
+    pub playoff: Option<Playoff>
+}
+
+/// Divisions from the top down, with `movements[n]` between `divisions[n]`
+/// and `divisions[n + 1]`. Read from `divisions.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pyramid
+{
+    pub divisions: Vec<Division>,
+    #[serde(default)]
+    pub movements: Vec<Movement>
+}
+
+/// A two-legged playoff tie, `team_a` being the better seed and at home in the second leg.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayoffTie
+{
+    pub round: u32,
+    pub team_a: u32,
+    pub team_b: u32,
+    pub legs: Vec<Match>,
+    pub winner: u32
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayoffBracket
+{
+    /// Index of the upper division of the boundary the playoff is for.
+    pub upper_division: usize,
+    pub ties: Vec<PlayoffTie>,
+    /// Teams that play in the upper division next season.
+    pub winners: Vec<u32>
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DivisionSeason
+{
+    pub name: String,
+    pub matches: Vec<Match>,
+    pub table: LeagueTable
+}
+
+/// A team changing division, `from` and `to` are division indexes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TeamMove
+{
+    pub team_id: u32,
+    pub from: usize,
+    pub to: usize
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PyramidSeason
+{
+    pub divisions: Vec<DivisionSeason>,
+    pub playoffs: Vec<PlayoffBracket>,
+    pub moves: Vec<TeamMove>,
+    /// The divisions for next season, after promotion and relegation.
+    pub next: Pyramid
+}
+
+impl Pyramid
+{
+    /// All teams in a single division, for a league without tiers.
+    pub fn single(teams: &[TeamWithPlayers]) -> Pyramid
+    {
+        return Pyramid {
+            divisions: vec![Division { name: "League".to_owned(), team_ids: teams.iter().map(|team| team.team.id).collect() }],
+            movements: Vec::new()
+        };
+    }
+
+    /// Check that every team plays in exactly one division, that there are
+    /// rules for every boundary and that the rules keep division sizes the same.
+    pub fn validate(&self, teams: &[TeamWithPlayers]) -> Result<()>
+    {
+        let mut problems: Vec<String> = Vec::new();
+        let mut seen: HashSet<u32> = HashSet::new();
+
+        for division in &self.divisions
+        {
+            for team_id in &division.team_ids
+            {
+                if !seen.insert(*team_id)
+                {
+                    problems.push(format!("team {} is in more than one division", team_id));
+                }
+
+                if !teams.iter().any(|team| team.team.id == *team_id)
+                {
+                    problems.push(format!("{} has unknown team {}", division.name, team_id));
+                }
+            }
+        }
+
+        for team in teams.iter().filter(|team| !seen.contains(&team.team.id))
+        {
+            problems.push(format!("team {} ({}) isn't in any division", team.team.id, team.team.name));
+        }
+
+        if self.movements.len() + 1 != self.divisions.len()
+        {
+            problems.push(format!("{} divisions need {} movements, got {}", self.divisions.len(), self.divisions.len().saturating_sub(1), self.movements.len()));
+        }
+
+        let mut used_places: Vec<HashSet<u32>> = vec![HashSet::new(); self.divisions.len()];
+
+        for (index, movement) in self.movements.iter().enumerate().filter(|(index, _)| index + 1 < self.divisions.len())
+        {
+            let upper_size = self.divisions[index].team_ids.len() as u32;
+            let lower_size = self.divisions[index + 1].team_ids.len() as u32;
+            let mut upper_places: Vec<u32> = (upper_size.saturating_sub(movement.relegated) + 1..=upper_size).collect();
+            let mut lower_places: Vec<u32> = (1..=movement.promoted).collect();
+
+            if movement.relegated != movement.promoted
+            {
+                problems.push(format!("below {}: {} relegated but {} promoted", self.divisions[index].name, movement.relegated, movement.promoted));
+            }
+
+            if let Some(playoff) = &movement.playoff
+            {
+                let participants = (playoff.upper_places.len() + playoff.lower_places.len()) as u32;
+
+                if playoff.upper_places.len() as u32 != playoff.spots
+                {
+                    problems.push(format!("below {}: the playoff needs as many upper division places as spots", self.divisions[index].name));
+                }
+
+                if playoff.spots == 0 || !participants.is_multiple_of(playoff.spots) || !(participants / playoff.spots).is_power_of_two() || participants == playoff.spots
+                {
+                    problems.push(format!("below {}: {} playoff teams can't be knocked out down to {}", self.divisions[index].name, participants, playoff.spots));
+                }
+
+                upper_places.extend(playoff.upper_places.iter());
+                lower_places.extend(playoff.lower_places.iter());
+            }
+
+            for (division, places, size) in [(index, upper_places, upper_size), (index + 1, lower_places, lower_size)]
+            {
+                for place in places
+                {
+                    if place == 0 || place > size || !used_places[division].insert(place)
+                    {
+                        problems.push(format!("{}: place {} can't be used for promotion or relegation", self.divisions[division].name, place));
+                    }
+                }
+            }
+        }
+
+        if problems.is_empty()
+        {
+            return Ok(());
+        }
+
+        return Err(Error::Validation(format!("invalid divisions: {}", problems.join(", "))));
+    }
+}
+
+/// Play a season in every division, the playoffs after it, and work out
+/// who goes up and down. Each division gets its own seed derived from `seed`.
+pub fn play_season(pyramid: &Pyramid, teams: &[TeamWithPlayers], strengths: &[TeamStrength], engine: &EngineConfig, tiebreakers: &[Tiebreaker], seed: u64) -> Result<PyramidSeason>
+{
+    pyramid.validate(teams)?;
+
+    let mut divisions: Vec<DivisionSeason> = Vec::new();
+
+    for (index, division) in pyramid.divisions.iter().enumerate()
+    {
+        let division_teams: Vec<TeamWithPlayers> = teams.iter().filter(|team| division.team_ids.contains(&team.team.id)).cloned().collect();
+        let division_strengths: Vec<TeamStrength> = strengths.iter().filter(|strength| division.team_ids.contains(&strength.team_id)).cloned().collect();
+        let plain_teams: Vec<Team> = division_teams.iter().map(|team| team.team.clone()).collect();
+
+        let season = simulation::simulate(&division_teams, &division_strengths, engine, rng::derive_seed(seed, index as u64))?;
+        let table: LeagueTable = LeagueTable::compute(&plain_teams, &season.matches, tiebreakers);
+
+        divisions.push(DivisionSeason { name: division.name.clone(), matches: season.matches, table });
+    }
+
+    let mut next: Pyramid = pyramid.clone();
+    let mut playoffs: Vec<PlayoffBracket> = Vec::new();
+    let mut moves: Vec<TeamMove> = Vec::new();
+
+    for (index, movement) in pyramid.movements.iter().enumerate()
+    {
+        let upper: &LeagueTable = &divisions[index].table;
+        let lower: &LeagueTable = &divisions[index + 1].table;
+        let mut going_down: Vec<u32> = upper.rows.iter().rev().take(movement.relegated as usize).map(|row| row.team_id).collect();
+        let mut going_up: Vec<u32> = lower.rows.iter().take(movement.promoted as usize).map(|row| row.team_id).collect();
+
+        if let Some(playoff) = &movement.playoff
+        {
+            let upper_seeds: Vec<u32> = places(upper, &playoff.upper_places);
+            let lower_seeds: Vec<u32> = places(lower, &playoff.lower_places);
+            let mut seeds: Vec<u32> = upper_seeds.clone();
+            seeds.extend(lower_seeds.iter());
+
+            let bracket: PlayoffBracket = play_playoff(index, seeds, playoff.spots, teams, strengths, engine, rng::derive_seed(seed, PLAYOFF_STREAM - index as u64))?;
+
+            going_down.extend(upper_seeds.iter().filter(|team_id| !bracket.winners.contains(team_id)));
+            going_up.extend(lower_seeds.iter().filter(|team_id| bracket.winners.contains(team_id)));
+            playoffs.push(bracket);
+        }
+
+        for team_id in going_down
+        {
+            next.divisions[index].team_ids.retain(|id| *id != team_id);
+            next.divisions[index + 1].team_ids.push(team_id);
+            moves.push(TeamMove { team_id, from: index, to: index + 1 });
+        }
+
+        for team_id in going_up
+        {
+            next.divisions[index + 1].team_ids.retain(|id| *id != team_id);
+            next.divisions[index].team_ids.push(team_id);
+            moves.push(TeamMove { team_id, from: index + 1, to: index });
+        }
+    }
+
+    return Ok(PyramidSeason { divisions, playoffs, moves, next });
+}
+
+/// Team ids at the given places of `table`, in the order of `places`.
+fn places(table: &LeagueTable, places: &[u32]) -> Vec<u32>
+{
+    return places.iter()
+        .filter_map(|place| table.rows.iter().find(|row| row.position == *place))
+        .map(|row| row.team_id)
+        .collect();
+}
+
+/// Knock `seeds` out in two-legged ties until `spots` teams are left. The
+/// better seed plays the second leg at home and goes through when the
+/// aggregate score is level.
+fn play_playoff(upper_division: usize, seeds: Vec<u32>, spots: u32, teams: &[TeamWithPlayers], strengths: &[TeamStrength], engine: &EngineConfig, seed: u64) -> Result<PlayoffBracket>
+{
+    let mut remaining: Vec<u32> = seeds;
+    let mut ties: Vec<PlayoffTie> = Vec::new();
+    let mut round: u32 = 1;
+    let mut leg_index: u64 = 0;
+
+    while remaining.len() > spots as usize
+    {
+        let mut through: Vec<u32> = Vec::new();
+
+        for pair in 0..remaining.len() / 2
+        {
+            let team_a: u32 = remaining[pair];
+            let team_b: u32 = remaining[remaining.len() - 1 - pair];
+
+            let first_leg: Match = simulation::play_match(teams, strengths, engine, team_b, team_a, rng::derive_seed(seed, leg_index))?;
+            let second_leg: Match = simulation::play_match(teams, strengths, engine, team_a, team_b, rng::derive_seed(seed, leg_index + 1))?;
+            leg_index += 2;
+
+            let (first_b, first_a) = first_leg.score();
+            let (second_a, second_b) = second_leg.score();
+            let winner: u32 = if first_b + second_b > first_a + second_a { team_b } else { team_a };
+
+            through.push(winner);
+            ties.push(PlayoffTie { round, team_a, team_b, legs: vec![first_leg, second_leg], winner });
+        }
+
+        //Winners keep the seeding order they came in with.
+        remaining.retain(|team_id| through.contains(team_id));
+        round += 1;
+    }
+
+    return Ok(PlayoffBracket { upper_division, ties, winners: remaining });
+}
+
+impl PyramidSeason
+{
+    pub fn print(&self, teams: &[TeamWithPlayers])
+    {
+        let name = |team_id: u32| -> String {
+            return teams.iter().find(|team| team.team.id == team_id).map(|team| team.team.name.clone()).unwrap_or_default();
+        };
+
+        for division in &self.divisions
+        {
+            println!("{}", division.name);
+            division.table.print();
+            println!();
+        }
+
+        for bracket in &self.playoffs
+        {
+            println!("Playoffs below {}", self.divisions[bracket.upper_division].name);
+
+            for tie in &bracket.ties
+            {
+                let (first_b, first_a) = tie.legs[0].score();
+                let (second_a, second_b) = tie.legs[1].score();
+
+                println!("  Round {}: {} - {} {}-{} (agg.), {} through",
+                    tie.round, name(tie.team_a), name(tie.team_b), first_a + second_a, first_b + second_b, name(tie.winner));
+            }
+
+            println!();
+        }
+
+        for team_move in &self.moves
+        {
+            let direction = if team_move.to < team_move.from { "promoted to" } else { "relegated to" };
+            println!("{} {} {}", name(team_move.team_id), direction, self.divisions[team_move.to].name);
+        }
+    }
+}
@@ -1,26 +1,23 @@
+use chrono::{ Datelike, NaiveDate };
+use rand::Rng;
 use serde::{ Serialize, Deserialize };
+use std::convert::TryFrom;
 use std::str::{ FromStr };
-use rand::Rng;
+use crate::tactics::TeamSetup;
+use crate::error::Error;
 
 
-#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[derive(Eq, PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
 pub enum Position 
 {
     Attacker,
     Midfield,
     Defender,
     Goalkeeper,
+    #[default]
     NotOnTheField
 }
 
-impl Default for Position
-{
-    fn default() -> Self
-    {
-        Position::NotOnTheField
-    }
-}
-
 impl FromStr for Position 
 {
     type Err = ();
@@ -37,20 +34,169 @@ impl FromStr for Position
     }
 }
 
+/// Player ratings, each from 0 to 100.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Attributes
+{
+    pub finishing: u8,
+    pub passing: u8,
+    pub tackling: u8,
+    pub pace: u8,
+    pub goalkeeping: u8,
+    /// How slowly the player tires and how quickly they recover.
+    pub stamina: u8
+}
+
+/// Stamina of players from files written before it was tracked.
+pub const DEFAULT_STAMINA: u8 = 70;
+/// Goalkeeping of outfield players from files with only a `quality`.
+const OUTFIELD_GOALKEEPING: u8 = 20;
+
+impl Attributes
+{
+    /// Attributes for a player from a file with a single `quality`: every
+    /// attribute is set to it, except goalkeeping for outfield players.
+    pub fn from_quality(quality: u8, position: &Position, stamina: u8) -> Attributes
+    {
+        let goalkeeping: u8 = if *position == Position::Goalkeeper { quality } else { OUTFIELD_GOALKEEPING.min(quality) };
+
+        return Attributes { finishing: quality, passing: quality, tackling: quality, pace: quality, goalkeeping, stamina };
+    }
+
+    /// Random attributes spread around a `base` level, with little
+    /// goalkeeping for outfield players.
+    pub fn roll(base: i32, position: &Position, rng: &mut impl Rng) -> Attributes
+    {
+        let mut roll = || -> u8 { (base + rng.gen_range(-10..=10)).clamp(1, 99) as u8 };
+
+        let finishing = roll();
+        let passing = roll();
+        let tackling = roll();
+        let pace = roll();
+        let goalkeeping = if *position == Position::Goalkeeper { roll() } else { rng.gen_range(5..30) };
+        let stamina = 50 + rng.gen_range(0..50);
+
+        return Attributes { finishing, passing, tackling, pace, goalkeeping, stamina };
+    }
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(try_from = "PlayerRecord")]
 pub struct Player
 {
     pub id: u32,
     pub name: String,
-    pub dob: String,
+    /// Date of birth, `None` when the crawl didn't know it.
+    pub dob: Option<NaiveDate>,
     pub height: String,
     pub position: Position,
-    pub quality: u8
+    pub attributes: Attributes,
+    /// Name of the club the player really plays for, when the crawl knows it.
+    #[serde(default)]
+    pub club: Option<String>
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Parse a date of birth as crawled, e.g. "21.07.1992", or as stored,
+/// e.g. "1992-07-21". Unknown dates such as "???" give `None`.
+pub fn parse_dob(dob: &str) -> Option<NaiveDate>
+{
+    return NaiveDate::parse_from_str(dob.trim(), "%d.%m.%Y")
+        .or_else(|_| NaiveDate::parse_from_str(dob.trim(), "%Y-%m-%d"))
+        .ok();
+}
+
+impl Player
+{
+    /// Age in whole years on `date`, when the date of birth is known.
+    pub fn age_on(&self, date: NaiveDate) -> Option<u32>
+    {
+        let dob: NaiveDate = self.dob?;
+
+        if date < dob
+        {
+            return Some(0);
+        }
+
+        let had_birthday = (date.month(), date.day()) >= (dob.month(), dob.day());
+        return Some((date.year() - dob.year()) as u32 - if had_birthday { 0 } else { 1 });
+    }
+
+    /// Overall rating at the player's own position.
+    pub fn rating(&self) -> u8
+    {
+        return self.rating_as(&self.position);
+    }
+
+    /// Overall rating when playing at `position`, weighing the attributes
+    /// that matter there.
+    pub fn rating_as(&self, position: &Position) -> u8
+    {
+        let attributes = &self.attributes;
+        let weighted = |parts: &[(u8, f32)]| -> u8 {
+            return parts.iter().map(|(value, weight)| *value as f32 * weight).sum::<f32>().round() as u8;
+        };
+
+        match position
+        {
+            Position::Attacker => weighted(&[(attributes.finishing, 0.5), (attributes.pace, 0.3), (attributes.passing, 0.2)]),
+            Position::Midfield => weighted(&[(attributes.passing, 0.5), (attributes.tackling, 0.2), (attributes.pace, 0.15), (attributes.finishing, 0.15)]),
+            Position::Defender => weighted(&[(attributes.tackling, 0.55), (attributes.pace, 0.25), (attributes.passing, 0.2)]),
+            Position::Goalkeeper => weighted(&[(attributes.goalkeeping, 0.85), (attributes.passing, 0.15)]),
+            Position::NotOnTheField => weighted(&[(attributes.finishing, 0.25), (attributes.passing, 0.25), (attributes.tackling, 0.25), (attributes.pace, 0.25)])
+        }
+    }
+}
+
+/// A player as stored on disk. Files from before attributes existed have a
+/// single `quality` and maybe a `stamina` instead.
+#[derive(Deserialize)]
+struct PlayerRecord
+{
+    id: u32,
+    name: String,
+    #[serde(default)]
+    dob: Option<String>,
+    height: String,
+    position: Position,
+    #[serde(default)]
+    attributes: Option<Attributes>,
+    #[serde(default)]
+    quality: Option<u8>,
+    #[serde(default)]
+    stamina: Option<u8>,
+    #[serde(default)]
+    club: Option<String>
+}
+
+impl TryFrom<PlayerRecord> for Player
+{
+    type Error = String;
+
+    fn try_from(record: PlayerRecord) -> Result<Self, Self::Error>
+    {
+        let attributes: Attributes = match (record.attributes, record.quality)
+        {
+            (Some(attributes), _) => attributes,
+            (None, Some(quality)) => Attributes::from_quality(quality, &record.position, record.stamina.unwrap_or(DEFAULT_STAMINA)),
+            (None, None) => { return Err(format!("player {} has neither attributes nor a quality", record.id)); }
+        };
+
+        return Ok(Player {
+            id: record.id,
+            name: record.name,
+            dob: record.dob.as_deref().and_then(parse_dob),
+            height: record.height,
+            position: record.position,
+            attributes,
+            club: record.club
+        });
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub enum Surface
 {
+    #[default]
     Unknown,
     Grass,
     ArtificialGrass,
@@ -64,18 +210,14 @@ impl FromStr for Surface
     fn from_str(string: &str) -> Result<Self, Self::Err>
     {
         match string.to_lowercase().as_ref() {
-            "grass" => { Ok(Surface::Grass) },
-            "kunstgras" => { Ok(Surface::ArtificialGrass) },
-            "hybride" => { Ok(Surface::Hybrid) },
+            "grass" | "gras" | "natuurgras" | "rasen" | "naturrasen" => { Ok(Surface::Grass) },
+            "kunstgras" | "artificial grass" | "artificialgrass" | "kunstrasen" => { Ok(Surface::ArtificialGrass) },
+            "hybride" | "hybrid" | "hybridrasen" => { Ok(Surface::Hybrid) },
             _ => { Ok(Surface::Unknown) }
         }
     }
 }
 
-impl Default for Surface {
-    fn default() -> Self { Surface::Unknown }
-}
-
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct Team
 {
@@ -84,65 +226,276 @@ pub struct Team
     pub city: String,
     pub stadium: String,
     pub surface: Surface,
-    pub since: u32
+    /// Year the team has played in the league since, 0 when unknown.
+    pub since: u32,
+    /// Year the club was founded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub founded: Option<u32>
 }
 
-#[derive(Clone, Default, Debug, Serialize, Deserialize)]
-pub struct TeamWithPlayers
+fn default_form() -> f32
 {
-    pub team: Team,
-    pub players: Vec<Player>
+    1.0
+}
+
+fn is_zero(value: &u32) -> bool
+{
+    return *value == 0;
 }
 
-impl TeamWithPlayers 
+/// Match minute as shown on a scoreboard, "23" or "45+2" in stoppage time.
+/// `minute` counts from 0, the scoreboard from 1.
+pub fn match_minute(minute: u32, added_time: u32) -> String
 {
-    pub fn get_random_player(&self, position: Position) -> Player
+    if added_time == 0
     {
-        let mut player: Player = Player{ ..Default::default() };
-        let mut index = 0;
+        return (minute + 1).to_string();
+    }
+
+    return format!("{}+{}", minute, added_time);
+}
 
-        let mut rng = rand::thread_rng();
+/// How strong a team is rated going into the season, keyed by `Team.id`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TeamStrength
+{
+    pub team_id: u32,
+    pub rating: u32,
+    /// Multiplier on `rating` for current form, 1.0 is neutral.
+    #[serde(default = "default_form")]
+    pub form: f32,
+    /// When the rating was taken, e.g. "2021-10-29".
+    #[serde(default)]
+    pub source_date: String
+}
+
+impl TeamStrength
+{
+    pub fn effective_rating(&self) -> f32
+    {
+        return self.rating as f32 * self.form;
+    }
+
+    pub fn find(strengths: &[TeamStrength], team_id: u32) -> Option<&TeamStrength>
+    {
+        return strengths.iter().find(|strength| strength.team_id == team_id);
+    }
 
-        loop
+    /// Check that there is exactly one strength record for every team.
+    pub fn validate(strengths: &[TeamStrength], teams: &[Team]) -> Result<(), Error>
+    {
+        let mut problems: Vec<String> = Vec::new();
+
+        for team in teams
         {
-            let current_player = self.players.get(index);
+            match strengths.iter().filter(|strength| strength.team_id == team.id).count()
+            {
+                0 => { problems.push(format!("no strength for team {} ({})", team.id, team.name)); },
+                1 => { },
+                count => { problems.push(format!("{} strengths for team {} ({})", count, team.id, team.name)); }
+            }
+        }
 
-            if let Some(current_found_player) = current_player
+        for strength in strengths
+        {
+            if !teams.iter().any(|team| team.id == strength.team_id)
             {
-                if current_found_player.position == position 
-                {
-                    let random = rng.gen_range(0..100);
-
-                    if random > 90 
-                    {
-                        player = current_found_player.clone();
-                        break;
-                    }
-                }
+                problems.push(format!("strength for unknown team {}", strength.team_id));
             }
+        }
 
-            index = (index + 1) % self.players.len();
+        if problems.is_empty()
+        {
+            return Ok(());
         }
 
-        return player;
+        return Err(Error::Validation(format!("team strengths don't match the teams: {}", problems.join(", "))));
     }
 }
 
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct TeamWithPlayers
+{
+    pub team: Team,
+    pub players: Vec<Player>,
+    #[serde(default)]
+    pub setup: TeamSetup
+}
+
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct Goal
 {
     pub time: u32,
     pub team_id: u32,
-    pub player_id: u32
+    pub player_id: u32,
+    #[serde(default)]
+    pub assist_player_id: Option<u32>,
+    /// Minutes into stoppage time, `time` is then the end of the half.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub added_time: u32
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum CardKind
+{
+    Yellow,
+    /// A second yellow in the same match, the player is sent off.
+    SecondYellow,
+    Red
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Card
+{
+    pub time: u32,
+    pub team_id: u32,
+    pub player_id: u32,
+    pub kind: CardKind,
+    /// Minutes into stoppage time, `time` is then the end of the half.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub added_time: u32
+}
+
+impl Card
+{
+    pub fn is_sending_off(&self) -> bool
+    {
+        return self.kind != CardKind::Yellow;
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Severity
+{
+    Minor,
+    Moderate,
+    Serious
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Injury
+{
+    pub time: u32,
+    pub team_id: u32,
+    pub player_id: u32,
+    pub severity: Severity,
+    /// Days the player can't be picked, counted from the day of the match.
+    pub days_out: u32,
+    /// Minutes into stoppage time, `time` is then the end of the half.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub added_time: u32
+}
+
+/// One kick of a penalty shootout.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PenaltyKick
+{
+    pub team_id: u32,
+    pub taker_id: u32,
+    pub keeper_id: u32,
+    pub scored: bool
+}
+
+/// Penalties scored by both teams in a shootout, and every kick in the order taken.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Shootout
+{
+    pub home_goals: u32,
+    pub out_goals: u32,
+    #[serde(default)]
+    pub kicks: Vec<PenaltyKick>
+}
+
+/// Part of the pitch an event happened in, named after whose goal is nearest.
+/// Follows the engine's field: 0 is the home goal, 100 the out goal.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Zone
+{
+    HomeGoal,
+    HomeHalf,
+    Midfield,
+    OutHalf,
+    OutGoal
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum EventKind
+{
+    KickOff,
+    /// The team won the ball battle in midfield and pushed forward.
+    MidfieldWon,
+    /// The attacking team got past the defence towards goal.
+    AttackAdvanced,
+    /// The defending team won the ball back and pushed the attack out.
+    AttackRepelled,
+    /// The keeper stopped a shot, the ball rebounds back into play.
+    Save,
+    Goal,
+    /// `player_ids` holds the player going off, then the one coming on.
+    Substitution,
+    YellowCard,
+    /// A straight red or a second yellow.
+    RedCard,
+    /// `player_ids` holds the injured player, a substitution follows when one is left.
+    Injury,
+    /// How tired everyone on the pitch is, logged every quarter of an hour.
+    Fatigue,
+    /// `team_id` was left with too few players and the match was abandoned.
+    Abandoned
+}
+
+/// One step of the match engine. `team_id` is the team that came out of the
+/// event with the ball, `field` is where the ball ended up afterwards.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MatchEvent
+{
+    pub minute: u32,
+    pub zone: Zone,
+    pub team_id: u32,
+    pub kind: EventKind,
+    pub player_ids: Vec<u32>,
+    pub field: i32,
+    /// Fatigue of the players in `player_ids`, at the same index, from 0.0 fresh
+    /// to 1.0 exhausted. Only filled in for fatigue and substitution events.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fatigue: Vec<f32>,
+    /// Minutes into stoppage time, `minute` is then the end of the half.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub added_time: u32
 }
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct Match 
 {
+    /// 0 in files written before matches were scheduled by matchday.
+    #[serde(default)]
+    pub matchday: u32,
     pub team_home: u32,
     pub team_out: u32,
     pub goals: Vec<Goal>,
+    #[serde(default)]
+    pub events: Vec<MatchEvent>,
+    /// Ids of the starting eleven of both teams.
+    #[serde(default)]
+    pub home_lineup: Vec<u32>,
+    #[serde(default)]
+    pub out_lineup: Vec<u32>,
+    #[serde(default)]
+    pub cards: Vec<Card>,
+    #[serde(default)]
+    pub injuries: Vec<Injury>,
+    /// Set when the match went to extra time.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub extra_time: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shootout: Option<Shootout>,
+    /// Stoppage time added to every half played, in minutes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stoppage: Vec<u32>,
+    /// Team left with too few players to go on, the match is awarded to the other team.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forfeited_by: Option<u32>
 }
 
 impl Match 
@@ -152,7 +505,46 @@ impl Match
         self.goals.push(goal);
     }
 
-    pub fn get_team(&self, teams: &Vec<TeamWithPlayers>, id: u32) -> Option<TeamWithPlayers>
+    pub fn add_event(&mut self, minute: u32, zone: Zone, team_id: u32, kind: EventKind, player_ids: Vec<u32>, field: i32)
+    {
+        self.events.push(MatchEvent { minute, zone, team_id, kind, player_ids, field, fatigue: Vec::new(), added_time: 0 });
+    }
+
+    /// Final score as (home goals, out goals). A forfeited match is awarded
+    /// 3-0, unless the other team was already winning by more.
+    pub fn score(&self) -> (u32, u32)
+    {
+        const AWARDED_GOALS: u32 = 3;
+
+        let home_goals = self.goals.iter().filter(|goal| goal.team_id == self.team_home).count() as u32;
+        let out_goals = self.goals.iter().filter(|goal| goal.team_id == self.team_out).count() as u32;
+
+        match self.forfeited_by
+        {
+            Some(team_id) if team_id == self.team_home && out_goals < home_goals + AWARDED_GOALS => { return (0, AWARDED_GOALS); },
+            Some(team_id) if team_id == self.team_out && home_goals < out_goals + AWARDED_GOALS => { return (AWARDED_GOALS, 0); },
+            _ => { return (home_goals, out_goals); }
+        }
+    }
+
+    /// Id of the team that won, on goals or on penalties. `None` for a draw.
+    pub fn winner(&self) -> Option<u32>
+    {
+        let (home_goals, out_goals) = match &self.shootout
+        {
+            Some(shootout) => (shootout.home_goals, shootout.out_goals),
+            None => self.score()
+        };
+
+        if home_goals == out_goals
+        {
+            return None;
+        }
+
+        return Some(if home_goals > out_goals { self.team_home } else { self.team_out });
+    }
+
+    pub fn get_team(&self, teams: &[TeamWithPlayers], id: u32) -> Option<TeamWithPlayers>
     {
         for team in teams 
         {
@@ -165,7 +557,7 @@ impl Match
         None
     }
 
-    pub fn get_players(&self, teams: &Vec<TeamWithPlayers>) -> (Vec<Player>, Vec<Player>)
+    pub fn get_players(&self, teams: &[TeamWithPlayers]) -> (Vec<Player>, Vec<Player>)
     {   
         let mut team_home_players: Vec<Player> = Vec::new();
         let mut team_out_players: Vec<Player> = Vec::new();
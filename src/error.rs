@@ -0,0 +1,53 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Everything that can go wrong while loading data, crawling or simulating.
+#[derive(Debug)]
+pub enum Error
+{
+    /// Reading or writing a file failed.
+    Io(PathBuf, std::io::Error),
+    /// A data file isn't valid JSON for what we expected in it.
+    Json(PathBuf, serde_json::Error),
+    /// The inputs load fine but don't fit together.
+    Validation(String),
+    /// Fetching or parsing a crawled page failed.
+    Crawl(String)
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            Error::Io(path, error) => write!(formatter, "{}: {}", path.display(), error),
+            Error::Json(path, error) => write!(formatter, "{}: invalid JSON: {}", path.display(), error),
+            Error::Validation(message) => write!(formatter, "invalid data: {}", message),
+            Error::Crawl(message) => write!(formatter, "crawl failed: {}", message)
+        }
+    }
+}
+
+impl std::error::Error for Error
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+    {
+        match self
+        {
+            Error::Io(_, error) => Some(error),
+            Error::Json(_, error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error
+{
+    fn from(error: reqwest::Error) -> Self
+    {
+        Error::Crawl(error.to_string())
+    }
+}
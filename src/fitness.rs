@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use crate::entities::{ DEFAULT_STAMINA, TeamWithPlayers };
+use crate::side::Side;
+
+/// Fatigue players carry from one match into the next. Players who aren't
+/// listed are fully rested.
+#[derive(Clone, Default, Debug)]
+pub struct Fitness
+{
+    fatigue: HashMap<u32, f32>
+}
+
+impl Fitness
+{
+    pub fn fatigue(&self, player_id: u32) -> f32
+    {
+        return self.fatigue.get(&player_id).copied().unwrap_or(0.0);
+    }
+
+    /// Remember how tired everyone who played for `side` finished the match.
+    pub fn record(&mut self, side: &Side)
+    {
+        for (player_id, fatigue) in side.fatigue_levels()
+        {
+            self.fatigue.insert(player_id, fatigue);
+        }
+    }
+
+    /// Let every player rest for `days`. Players with more stamina recover
+    /// faster, an average player sheds `per_day` fatigue a day.
+    pub fn recover(&mut self, teams: &[TeamWithPlayers], days: u32, per_day: f32)
+    {
+        let stamina: HashMap<u32, u8> = teams.iter()
+            .flat_map(|team| team.players.iter())
+            .map(|player| (player.id, player.attributes.stamina))
+            .collect();
+
+        for (player_id, fatigue) in self.fatigue.iter_mut()
+        {
+            let player_stamina = stamina.get(player_id).copied().unwrap_or(DEFAULT_STAMINA);
+            let recovery = per_day * days as f32 * (0.5 + player_stamina as f32 / 100.0) / (0.5 + DEFAULT_STAMINA as f32 / 100.0);
+
+            *fatigue -= recovery;
+        }
+
+        self.fatigue.retain(|_, fatigue| *fatigue > 0.0);
+    }
+}
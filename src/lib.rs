@@ -0,0 +1,22 @@
+#![allow(clippy::needless_return)]
+
+pub mod entities;
+pub mod config;
+pub mod error;
+pub mod crawler;
+pub mod simulation;
+pub mod rng;
+pub mod table;
+pub mod montecarlo;
+pub mod squad;
+pub mod lineup;
+pub mod side;
+pub mod fitness;
+pub mod absence;
+pub mod career;
+pub mod divisions;
+pub mod cup;
+pub mod tactics;
+pub mod storage;
+
+pub use error::{ Error, Result };
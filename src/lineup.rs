@@ -0,0 +1,74 @@
+use crate::entities::{ Player, Position };
+use crate::tactics::Formation;
+
+/// The eleven who start a match and the players on the bench.
+#[derive(Clone, Default, Debug)]
+pub struct Lineup
+{
+    pub starters: Vec<Player>,
+    pub bench: Vec<Player>
+}
+
+/// Pick the best eleven for `formation`: one keeper and the strongest players
+/// for every line. A line that can't be filled from its own position is
+/// topped up with the best remaining outfield players. Players in
+/// `unavailable` are left out of the match entirely.
+pub fn pick_lineup(players: &[Player], formation: Formation, unavailable: &[u32]) -> Lineup
+{
+    let (defenders, midfielders, attackers) = formation.lines();
+    let lines: [(Position, u32); 4] = [
+        (Position::Goalkeeper, 1),
+        (Position::Defender, defenders),
+        (Position::Midfield, midfielders),
+        (Position::Attacker, attackers)
+    ];
+
+    let mut available: Vec<Player> = players.iter()
+        .filter(|player| !unavailable.contains(&player.id))
+        .cloned()
+        .collect();
+
+    //Best players first, so taking the first match of a position takes the best one.
+    available.sort_by_key(|player| std::cmp::Reverse(player.rating()));
+
+    let mut starters: Vec<Player> = Vec::new();
+    let mut missing: u32 = 0;
+
+    for (position, count) in lines.iter()
+    {
+        for _ in 0..*count
+        {
+            match available.iter().position(|player| player.position == *position)
+            {
+                Some(index) => { starters.push(available.remove(index)); },
+                None => { missing += 1; }
+            }
+        }
+    }
+
+    for _ in 0..missing
+    {
+        match available.iter().position(|player| player.position != Position::Goalkeeper)
+        {
+            Some(index) => { starters.push(available.remove(index)); },
+            None => { break; }
+        }
+    }
+
+    return Lineup { starters, bench: available };
+}
+
+/// Best bench player for `position`, or the best bench outfield player when
+/// nobody on the bench plays there.
+pub fn best_replacement(bench: &[Player], position: &Position) -> Option<usize>
+{
+    let best_of = |matches: &dyn Fn(&Player) -> bool| -> Option<usize> {
+        bench.iter().enumerate()
+            .filter(|(_, player)| matches(player))
+            .max_by_key(|(index, player)| (player.rating_as(position), std::cmp::Reverse(*index)))
+            .map(|(index, _)| index)
+    };
+
+    return best_of(&|player: &Player| player.position == *position)
+        .or_else(|| best_of(&|player: &Player| *position != Position::Goalkeeper && player.position != Position::Goalkeeper));
+}
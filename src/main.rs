@@ -1,208 +1,810 @@
-mod secret;
-mod entities;
-mod crawler;
-mod simulation;
+#![allow(clippy::needless_return)]
 
-use std::path::{Path};
-use std::fs::File;
-use std::io::Read;
-use entities::{Match, Goal, Position, Player, Team, TeamWithPlayers};
-use rand::Rng;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use soccer_sim::entities::{Match, Player, Team, TeamStrength, TeamWithPlayers};
+use soccer_sim::table::{self, LeagueTable, Tiebreaker};
+use soccer_sim::montecarlo::{self, Projection, ProjectionConfig};
+use soccer_sim::config::EngineConfig;
+use soccer_sim::squad::{SquadConfig, SquadSource};
+use soccer_sim::tactics::{self, Formation, Tactics, TeamSetup, TeamTactics};
+use soccer_sim::career::{self, Career, CareerConfig};
+use soccer_sim::crawler::{CrawlConfig, CrawledClubs, CrawledPlayers, Pagination};
+use soccer_sim::crawler::fetch::{DirFetcher, HttpFetcher};
+use soccer_sim::crawler::stub::StubServer;
+use soccer_sim::cup::{self, Cup, CupConfig, Draw};
+use soccer_sim::divisions::{self, Pyramid, PyramidSeason};
+use soccer_sim::simulation::Season;
+use soccer_sim::{absence, crawler, rng, simulation, squad, storage, Error};
 
-fn load_players() -> Vec<Player>
+//Sub-streams of the master seed, one per stage that consumes randomness.
+const CRAWL_STREAM: u64 = u64::MAX;
+const SQUAD_STREAM: u64 = u64::MAX - 1;
+const FRIENDLY_STREAM: u64 = u64::MAX - 2;
+
+#[derive(Parser)]
+#[command(name = "soccer-sim", about = "Simulate a soccer league from crawled player data")]
+struct Cli
+{
+    /// Directory holding the JSON data files.
+    #[arg(long, global = true, default_value = "./data")]
+    data_dir: PathBuf,
+
+    /// Master seed. A random one is picked and printed when omitted.
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+
+    /// JSON file with match engine settings, see `EngineConfig`.
+    #[arg(long, global = true)]
+    engine_config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command
+}
+
+/// Crawl settings on top of <data-dir>/crawl.json, see `CrawlConfig`.
+#[derive(Args)]
+struct CrawlArgs
+{
+    /// Crawl config file to use instead of <data-dir>/crawl.json.
+    #[arg(long)]
+    crawl_config: Option<PathBuf>,
+
+    /// URL of the player list without the page number.
+    #[arg(long)]
+    base_url: Option<String>,
+
+    /// Last page to crawl, otherwise crawl until a page has no players.
+    #[arg(long)]
+    max_pages: Option<u32>,
+
+    /// How pages are numbered: path, query:<param> or offset:<param>.
+    #[arg(long, value_parser = parse_pagination)]
+    pagination: Option<Pagination>,
+
+    /// Players per page.
+    #[arg(long)]
+    per_page: Option<u32>,
+
+    /// Milliseconds to wait between requests.
+    #[arg(long)]
+    delay_ms: Option<u64>,
+
+    #[arg(long)]
+    user_agent: Option<String>,
+
+    /// Milliseconds before a request is given up on.
+    #[arg(long)]
+    timeout_ms: Option<u64>,
+
+    /// Times a failed request is tried again.
+    #[arg(long)]
+    retries: Option<u32>,
+
+    /// Keep fetched pages here and revalidate them on the next crawl.
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+
+    /// Crawl pages even when robots.txt disallows them.
+    #[arg(long)]
+    ignore_robots: bool,
+
+    /// Link to the page listing the clubs, a path on the site or a full URL.
+    #[arg(long)]
+    clubs_url: Option<String>
+}
+
+#[derive(Subcommand)]
+enum Command
+{
+    /// Crawl all players from the source site into players.json.
+    Crawl
+    {
+        /// Where to write the players, defaults to <data-dir>/players.json.
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Read saved pages (1.html, 2.html, ...) from this directory instead of the site.
+        #[arg(long)]
+        pages_dir: Option<PathBuf>,
+
+        /// Serve --pages-dir from a local HTTP server and crawl that.
+        #[arg(long, requires = "pages_dir")]
+        serve: bool,
+
+        #[command(flatten)]
+        source: CrawlArgs
+    },
+
+    /// Crawl the clubs with their squads into teams.json and players.json.
+    CrawlClubs
+    {
+        /// Where to write the players, defaults to <data-dir>/players.json.
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Where to write the teams, defaults to <data-dir>/teams.json. Clubs
+        /// already in the file keep their ids, and the default file is only
+        /// replaced when strengths.json and divisions.json still fit.
+        #[arg(long)]
+        teams_output: Option<PathBuf>,
+
+        /// Read saved pages from this directory instead of the site, the
+        /// page of a link like /teams/ajax/ from teams/ajax.html.
+        #[arg(long)]
+        pages_dir: Option<PathBuf>,
+
+        /// Serve --pages-dir from a local HTTP server and crawl that. The
+        /// clubs URL has to be a path then.
+        #[arg(long, requires = "pages_dir")]
+        serve: bool,
+
+        #[command(flatten)]
+        source: CrawlArgs
+    },
+
+    /// Assign players to teams and write team_with_players.json.
+    BuildSquads
+    {
+        /// Where to write the squads, defaults to <data-dir>/team_with_players.json.
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        #[arg(long, default_value_t = 5)]
+        attackers: u32,
+
+        #[arg(long, default_value_t = 6)]
+        midfielders: u32,
+
+        #[arg(long, default_value_t = 7)]
+        defenders: u32,
+
+        #[arg(long, default_value_t = 2)]
+        goalkeepers: u32,
+
+        /// Put players in the club they really play for instead of a random one.
+        #[arg(long)]
+        real_clubs: bool,
+
+        /// Formation every team starts with, e.g. 4-4-2.
+        #[arg(long, default_value = "4-3-3", value_parser = parse_formation)]
+        formation: Formation,
+
+        /// Tactics every team starts with: attacking, balanced, defensive, counter or high-press.
+        #[arg(long, default_value = "balanced", value_parser = parse_tactics)]
+        tactics: Tactics
+    },
+
+    /// Play a full season, writing matches.json, table.json and absences.json.
+    SimulateSeason
+    {
+        /// Where to write the matches, defaults to <data-dir>/matches.json.
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Where to write the final table, defaults to <data-dir>/table.json.
+        #[arg(long)]
+        table_output: Option<PathBuf>,
+
+        /// Also write every match event as CSV to this path.
+        #[arg(long)]
+        events_output: Option<PathBuf>,
+
+        /// Where to write the injuries and bans, defaults to <data-dir>/absences.json.
+        #[arg(long)]
+        absences_output: Option<PathBuf>,
+
+        /// Tiebreaker order, e.g. gd,gs,h2h.
+        #[arg(long, value_delimiter = ',', value_parser = parse_tiebreaker)]
+        tiebreakers: Option<Vec<Tiebreaker>>
+    },
+
+    /// Play a single match between two teams, given by id or name.
+    SimulateMatch
+    {
+        home: String,
+        away: String,
+
+        /// Print the minute-by-minute event log.
+        #[arg(long)]
+        events: bool,
+
+        /// Play it as a knockout match, with extra time and penalties when level.
+        #[arg(long)]
+        knockout: bool
+    },
+
+    /// Compute the table from an existing matches.json.
+    Table
+    {
+        /// Matches to read, defaults to <data-dir>/matches.json.
+        #[arg(long)]
+        matches: Option<PathBuf>,
+
+        /// Where to write the table, defaults to <data-dir>/table.json.
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Tiebreaker order, e.g. gd,gs,h2h.
+        #[arg(long, value_delimiter = ',', value_parser = parse_tiebreaker)]
+        tiebreakers: Option<Vec<Tiebreaker>>
+    },
+
+    /// Simulate the season many times and project where every team finishes.
+    Montecarlo
+    {
+        /// Number of seasons to simulate.
+        #[arg(long, default_value_t = 10_000)]
+        runs: u32,
+
+        /// Worker threads, defaults to the number of cores.
+        #[arg(long)]
+        threads: Option<usize>,
+
+        /// Where to write the JSON projection, defaults to <data-dir>/projection.json.
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Where to write the CSV projection, defaults to <data-dir>/projection.csv.
+        #[arg(long)]
+        csv_output: Option<PathBuf>,
+
+        /// Tiebreaker order, e.g. gd,gs,h2h.
+        #[arg(long, value_delimiter = ',', value_parser = parse_tiebreaker)]
+        tiebreakers: Option<Vec<Tiebreaker>>
+    },
+
+    /// Play a season in every division of divisions.json, with playoffs, promotion and relegation.
+    SimulateDivisions
+    {
+        /// Where to write the season, defaults to <data-dir>/divisions_season.json.
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Where to write next season's divisions, defaults to <data-dir>/divisions_next.json.
+        #[arg(long)]
+        next_output: Option<PathBuf>,
+
+        /// Tiebreaker order, e.g. gd,gs,h2h.
+        #[arg(long, value_delimiter = ',', value_parser = parse_tiebreaker)]
+        tiebreakers: Option<Vec<Tiebreaker>>
+    },
+
+    /// Play a knockout cup between all teams, with extra time and penalties for level ties.
+    SimulateCup
+    {
+        /// How ties are drawn: seeded by strength or random.
+        #[arg(long, default_value = "seeded", value_parser = parse_draw)]
+        draw: Draw,
+
+        /// Play every round but the final over two legs.
+        #[arg(long)]
+        two_legs: bool,
+
+        /// Where to write the matches, defaults to <data-dir>/cup_matches.json.
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Where to write the bracket, defaults to <data-dir>/cup_bracket.json.
+        #[arg(long)]
+        bracket_output: Option<PathBuf>
+    },
+
+    /// Play several seasons in a row, with players aging, retiring and coming through the youth ranks.
+    /// Uses the divisions in divisions.json when there is one.
+    Career
+    {
+        #[arg(long, default_value_t = 5)]
+        seasons: u32,
+
+        /// Start of the first season, e.g. 2021-08-01.
+        #[arg(long, default_value = "2021-08-01", value_parser = parse_date)]
+        start: NaiveDate,
+
+        /// Where to write the career, defaults to <data-dir>/career.json.
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Tiebreaker order, e.g. gd,gs,h2h.
+        #[arg(long, value_delimiter = ',', value_parser = parse_tiebreaker)]
+        tiebreakers: Option<Vec<Tiebreaker>>
+    }
+}
+
+fn parse_tiebreaker(value: &str) -> Result<Tiebreaker, String>
+{
+    return value.parse::<Tiebreaker>()
+        .map_err(|_| format!("unknown tiebreaker '{}', expected gd, gs or h2h", value));
+}
+
+fn parse_draw(value: &str) -> Result<Draw, String>
 {
-    const PLAYER_PATH: &str = "./data/players.json";
+    return value.parse::<Draw>()
+        .map_err(|_| format!("unknown draw '{}', expected seeded or random", value));
+}
+
+fn parse_pagination(value: &str) -> Result<Pagination, String>
+{
+    return value.parse::<Pagination>()
+        .map_err(|_| format!("unknown pagination '{}', expected path, query:<param> or offset:<param>", value));
+}
 
-    if !Path::new(PLAYER_PATH).exists()
+fn parse_date(value: &str) -> Result<NaiveDate, String>
+{
+    return NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("invalid date '{}', expected YYYY-MM-DD", value));
+}
+
+fn parse_formation(value: &str) -> Result<Formation, String>
+{
+    return value.parse::<Formation>()
+        .map_err(|_| format!("unknown formation '{}'", value));
+}
+
+fn parse_tactics(value: &str) -> Result<Tactics, String>
+{
+    return value.parse::<Tactics>()
+        .map_err(|_| format!("unknown tactics '{}'", value));
+}
+
+fn load_players(cli: &Cli, seed: u64) -> soccer_sim::Result<Vec<Player>>
+{
+    let mut crawl_rng = rng::from_seed(rng::derive_seed(seed, CRAWL_STREAM));
+    let crawl: CrawlConfig = load_crawl_config(cli, None)?;
+
+    return storage::load_players(&cli.data_dir.join("players.json"), &crawl, &mut crawl_rng);
+}
+
+/// Crawl config from --crawl-config or <data-dir>/crawl.json, defaults
+/// without either, with the flags given in `args` on top.
+fn load_crawl_config(cli: &Cli, args: Option<&CrawlArgs>) -> soccer_sim::Result<CrawlConfig>
+{
+    let path: PathBuf = args.and_then(|args| args.crawl_config.clone()).unwrap_or_else(|| cli.data_dir.join("crawl.json"));
+
+    let mut config: CrawlConfig = if path.exists() || args.is_some_and(|args| args.crawl_config.is_some())
     {
-        let players: Vec<Player> = crawler::crawl();
-        let json: String = serde_json::to_string_pretty(&players).unwrap();
-        std::fs::write(PLAYER_PATH, json).unwrap();
-        return players;
+        storage::load_json(&path)?
     }
     else
     {
-        let mut players_file = File::open(PLAYER_PATH).expect("Where is my players.json?");
+        CrawlConfig::default()
+    };
+
+    if let Some(args) = args
+    {
+        if let Some(base_url) = &args.base_url
+        {
+            config.base_url = base_url.clone();
+        }
+
+        if let Some(max_pages) = args.max_pages
+        {
+            config.max_pages = Some(max_pages);
+        }
 
-        let mut json = String::new();
-        players_file.read_to_string(&mut json).unwrap();
+        if let Some(pagination) = &args.pagination
+        {
+            config.pagination = pagination.clone();
+        }
+
+        if let Some(per_page) = args.per_page
+        {
+            config.per_page = per_page;
+        }
+
+        if let Some(delay_ms) = args.delay_ms
+        {
+            config.delay_ms = delay_ms;
+        }
 
-        let players: Vec<Player> = serde_json::from_str(&json).unwrap();
-        return players;
+        if let Some(user_agent) = &args.user_agent
+        {
+            config.user_agent = user_agent.clone();
+        }
+
+        if let Some(timeout_ms) = args.timeout_ms
+        {
+            config.timeout_ms = timeout_ms;
+        }
+
+        if let Some(retries) = args.retries
+        {
+            config.retries = retries;
+        }
+
+        if let Some(cache_dir) = &args.cache_dir
+        {
+            config.cache_dir = Some(cache_dir.clone());
+        }
+
+        if args.ignore_robots
+        {
+            config.respect_robots = false;
+        }
+
+        if let Some(clubs_url) = &args.clubs_url
+        {
+            config.clubs_url = Some(clubs_url.clone());
+        }
     }
+
+    return Ok(config);
 }
 
-fn load_teams() -> Vec<Team>
+fn build_squads(cli: &Cli, seed: u64, config: &SquadConfig, source: SquadSource, setup: TeamSetup, output: &Path) -> soccer_sim::Result<Vec<TeamWithPlayers>>
 {
-    const TEAMS_PATH: &str = "./data/teams.json";
+    let players: Vec<Player> = load_players(cli, seed)?;
+    let teams: Vec<Team> = storage::load_teams(&cli.data_dir.join("teams.json"))?;
+
+    let mut squad_rng = rng::from_seed(rng::derive_seed(seed, SQUAD_STREAM));
+    let mut team_players: Vec<TeamWithPlayers> = squad::build_squads(&players, &teams, config, source, &mut squad_rng)?;
 
-    if Path::new(TEAMS_PATH).exists()
+    for team in team_players.iter_mut()
     {
-        let mut teams_file = File::open(TEAMS_PATH)
-            .expect("Where is my teams.json?");
-    
-        let mut json = String::new();
-        teams_file.read_to_string(&mut json).unwrap();
-    
-        let teams: Vec<Team> = serde_json::from_str(&json).unwrap();
-        return teams;
+        team.setup = setup;
+    }
+
+    storage::save_json(output, &team_players)?;
+
+    return Ok(team_players);
+}
+
+/// Use the saved squads when there are any, otherwise build and save them.
+/// Setups from tactics.json, when present, override the saved ones.
+fn load_squads(cli: &Cli, seed: u64) -> soccer_sim::Result<Vec<TeamWithPlayers>>
+{
+    let path: PathBuf = cli.data_dir.join("team_with_players.json");
+    let tactics_path: PathBuf = cli.data_dir.join("tactics.json");
+
+    let mut team_players: Vec<TeamWithPlayers> = if path.exists()
+    {
+        storage::load_json(&path)?
     }
     else
     {
-        panic!("No teams.json was found!");
+        build_squads(cli, seed, &SquadConfig::default(), SquadSource::Random, TeamSetup::default(), &path)?
+    };
+
+    if tactics_path.exists()
+    {
+        let overrides: Vec<TeamTactics> = storage::load_json(&tactics_path)?;
+        tactics::apply_tactics(&mut team_players, &overrides)?;
     }
+
+    return Ok(team_players);
 }
 
-fn load_standings() -> Vec<u32>
+fn load_strengths(cli: &Cli, team_players: &[TeamWithPlayers]) -> soccer_sim::Result<Vec<TeamStrength>>
 {
-    const STANDING_PATH: &str = "./data/standings.json";
+    let teams: Vec<Team> = team_players.iter().map(|team| team.team.clone()).collect();
 
-    if Path::new(STANDING_PATH).exists()
+    return storage::load_strengths(
+        &cli.data_dir.join("strengths.json"),
+        &cli.data_dir.join("standings.json"),
+        &teams
+    );
+}
+
+/// Check that the strengths and divisions in the data directory, when there
+/// are any, still fit `teams` before teams.json is replaced with them.
+fn check_team_files(cli: &Cli, teams: &[Team]) -> soccer_sim::Result<()>
+{
+    let refuse = |file: &str, error: soccer_sim::Error| -> soccer_sim::Error {
+        let problem: String = match error
+        {
+            soccer_sim::Error::Validation(message) => message,
+            other => other.to_string()
+        };
+
+        return soccer_sim::Error::Validation(format!(
+            "not replacing teams.json, the crawled clubs don't fit {} ({}); update it or write the teams elsewhere with --teams-output", file, problem
+        ));
+    };
+
+    if cli.data_dir.join("strengths.json").exists() || cli.data_dir.join("standings.json").exists()
     {
-        let mut standings_file = File::open(STANDING_PATH)
-            .expect("Where is my standings.json?");
-    
-        let mut json = String::new();
-        standings_file.read_to_string(&mut json).unwrap();
-    
-        let standings: Vec<u32> = serde_json::from_str(&json).unwrap();
-        return standings;
+        storage::load_strengths(&cli.data_dir.join("strengths.json"), &cli.data_dir.join("standings.json"), teams)
+            .map_err(|error| refuse("strengths.json", error))?;
     }
-    else
+
+    let divisions_path: PathBuf = cli.data_dir.join("divisions.json");
+
+    if divisions_path.exists()
     {
-        panic!("No teams.json was found!");
+        let team_players: Vec<TeamWithPlayers> = teams.iter().map(|team| TeamWithPlayers { team: team.clone(), ..Default::default() }).collect();
+        let pyramid: Pyramid = storage::load_json(&divisions_path)?;
+        pyramid.validate(&team_players).map_err(|error| refuse("divisions.json", error))?;
     }
+
+    return Ok(());
 }
 
-fn make_teams(in_players: Vec<Player>, in_teams: Vec<Team>) -> Vec<TeamWithPlayers>
+/// Divisions from divisions.json, or all teams in one league without it.
+fn load_pyramid(cli: &Cli, team_players: &[TeamWithPlayers]) -> soccer_sim::Result<Pyramid>
 {
-    let mut teams = Vec::new();
-    for team in in_teams { teams.push(team); }
+    let path: PathBuf = cli.data_dir.join("divisions.json");
 
-    let mut players: Vec<Player> = Vec::new();
-    for player in in_players { players.push(player); }
+    if !path.exists()
+    {
+        return Ok(Pyramid::single(team_players));
+    }
 
-    let mut teams_with_players: Vec<TeamWithPlayers> = Vec::new();
+    return storage::load_json(&path);
+}
 
-    for team in teams 
+fn load_engine_config(cli: &Cli) -> soccer_sim::Result<EngineConfig>
+{
+    match &cli.engine_config
     {
-        let mut team_players = TeamWithPlayers {
-            team: team,
-            players: Vec::new()
-        };
+        Some(path) => storage::load_json(path),
+        None => Ok(EngineConfig::default())
+    }
+}
 
-        let mut attacker_count = 4;
-        let mut midfield_count = 3;
-        let mut defender_count = 3;
-        let mut goalie_count = 1;
+/// Find a team by id, or else by case-insensitive name.
+fn find_team(team_players: &[TeamWithPlayers], key: &str) -> soccer_sim::Result<u32>
+{
+    let found = team_players.iter().find(|team| {
+        key.parse::<u32>().map(|id| id == team.team.id).unwrap_or(false)
+            || team.team.name.eq_ignore_ascii_case(key)
+    });
 
-        let mut index = 0;
-        let mut rng = rand::thread_rng();
-        let mut used: Vec<usize> = Vec::new();
+    return found.map(|team| team.team.id)
+        .ok_or_else(|| Error::Validation(format!("no team with id or name '{}'", key)));
+}
 
-        while attacker_count > 0 || midfield_count > 0 || defender_count > 0 || goalie_count > 0
+fn run(cli: &Cli) -> soccer_sim::Result<()>
+{
+    let seed: u64 = cli.seed.unwrap_or_else(rng::random_seed);
+    let engine: EngineConfig = load_engine_config(cli)?;
+    let output_or = |output: &Option<PathBuf>, name: &str| output.clone().unwrap_or_else(|| cli.data_dir.join(name));
+
+    match &cli.command
+    {
+        Command::Crawl { output, pages_dir, serve, source } =>
         {
-            if used.contains(&index)
+            println!("Seed: {}", seed);
+
+            let mut crawl_rng = rng::from_seed(rng::derive_seed(seed, CRAWL_STREAM));
+            let config: CrawlConfig = load_crawl_config(cli, Some(source))?;
+
+            let crawled: CrawledPlayers = match pages_dir
+            {
+                Some(dir) if *serve =>
+                {
+                    //Saved pages are named by page number, so the stub uses path pagination.
+                    let server: StubServer = StubServer::start(dir)?;
+                    let stub_config = CrawlConfig { base_url: server.url(), pagination: Pagination::Path, ..config.clone() };
+                    crawler::crawl(&mut HttpFetcher::new(&stub_config)?, &config, &mut crawl_rng)?
+                },
+                Some(dir) => crawler::crawl(&mut DirFetcher::new(dir), &config, &mut crawl_rng)?,
+                None => crawler::crawl(&mut HttpFetcher::new(&config)?, &config, &mut crawl_rng)?
+            };
+            storage::save_json(&output_or(output, "players.json"), &crawled.players)?;
+            println!("Crawled {} players.", crawled.players.len());
+
+            if !crawled.skipped.is_empty()
             {
-                index = (index + 1) % players.len();
-                continue;
+                println!("Skipped {} table rows:", crawled.skipped.len());
+
+                for (page, row) in &crawled.skipped
+                {
+                    println!("  page {}, {}", page, row);
+                }
             }
+        },
+        Command::CrawlClubs { output, teams_output, pages_dir, serve, source } =>
+        {
+            println!("Seed: {}", seed);
+
+            let mut crawl_rng = rng::from_seed(rng::derive_seed(seed, CRAWL_STREAM));
+            let config: CrawlConfig = load_crawl_config(cli, Some(source))?;
 
-            if let Some(player) = players.get(index)
+            let mut crawled: CrawledClubs = match pages_dir
             {
-                let chance: u8 = rng.gen_range(0..100) as u8;
+                Some(dir) if *serve =>
+                {
+                    let server: StubServer = StubServer::start(dir)?;
+                    let stub_config = CrawlConfig { base_url: server.url(), ..config.clone() };
+                    crawler::crawl_clubs(&mut HttpFetcher::new(&stub_config)?, &config, &mut crawl_rng)?
+                },
+                Some(dir) => crawler::crawl_clubs(&mut DirFetcher::new(dir), &config, &mut crawl_rng)?,
+                None => crawler::crawl_clubs(&mut HttpFetcher::new(&config)?, &config, &mut crawl_rng)?
+            };
+            let teams_path: PathBuf = output_or(teams_output, "teams.json");
 
-                if chance > 97
+            if teams_path.exists()
+            {
+                crawled.keep_team_ids(&storage::load_teams(&teams_path)?);
+            }
+
+            if teams_output.is_none()
+            {
+                check_team_files(cli, &crawled.teams)?;
+            }
+
+            storage::save_json(&teams_path, &crawled.teams)?;
+            storage::save_json(&output_or(output, "players.json"), &crawled.players)?;
+            println!("Crawled {} clubs with {} players.", crawled.teams.len(), crawled.players.len());
+
+            if !crawled.skipped.is_empty()
+            {
+                println!("Skipped {} table rows:", crawled.skipped.len());
+
+                for (link, row) in &crawled.skipped
                 {
-                    match player.position 
-                    {
-                        Position::Attacker => 
-                        { 
-                            if attacker_count > 0
-                            {
-                                team_players.players.push(player.clone());
-                                attacker_count -= 1;
-                                used.push(index);
-                            }
-                        },
-                        Position::Midfield => 
-                        {
-                            if midfield_count > 0
-                            {
-                                team_players.players.push(player.clone());
-                                midfield_count -= 1;
-                                used.push(index);
-                            }
-                        },
-                        Position::Defender => 
-                        {
-                            if defender_count > 0
-                            {
-                                team_players.players.push(player.clone());
-                                defender_count -= 1;
-                                used.push(index);
-                            }
-                        },
-                        Position::Goalkeeper => 
-                        {
-                            if goalie_count > 0
-                            {
-                                team_players.players.push(player.clone());
-                                goalie_count -= 1;
-                                used.push(index);
-                            }
-                        }
-                        _ => { }
-                    }
+                    println!("  {}, {}", link, row);
                 }
             }
+        },
+        Command::BuildSquads { output, attackers, midfielders, defenders, goalkeepers, real_clubs, formation, tactics } =>
+        {
+            println!("Seed: {}", seed);
 
-            index = (index + 1) % players.len();
-        }
+            let config = SquadConfig {
+                attackers: *attackers,
+                midfielders: *midfielders,
+                defenders: *defenders,
+                goalkeepers: *goalkeepers
+            };
+            let source: SquadSource = if *real_clubs { SquadSource::RealClubs } else { SquadSource::Random };
 
-        teams_with_players.push(team_players);
-    }
+            let setup = TeamSetup { formation: *formation, tactics: *tactics };
 
-    return teams_with_players;
-}
+            let team_players: Vec<TeamWithPlayers> = build_squads(cli, seed, &config, source, setup, &output_or(output, "team_with_players.json"))?;
+            println!("Built {} squads.", team_players.len());
+        },
+        Command::SimulateSeason { output, table_output, events_output, absences_output, tiebreakers } =>
+        {
+            println!("Seed: {}", seed);
 
-fn save_team_players(team_players: &Vec<TeamWithPlayers>)
-{
-    const TEAM_PLAYER_PATH: &str = "./data/team_with_players.json";
+            let team_players: Vec<TeamWithPlayers> = load_squads(cli, seed)?;
+            let strengths: Vec<TeamStrength> = load_strengths(cli, &team_players)?;
 
-    if Path::new(TEAM_PLAYER_PATH).exists()
-    {
-        std::fs::remove_file(TEAM_PLAYER_PATH).unwrap();
-    }
+            let season: Season = simulation::simulate(&team_players, &strengths, &engine, seed)?;
+            let results: Vec<Match> = season.matches;
+            simulation::print_results(&team_players, &results);
+            storage::save_json(&output_or(output, "matches.json"), &results)?;
+            storage::save_json(&output_or(absences_output, "absences.json"), &season.absences)?;
 
-    let json: String = serde_json::to_string_pretty(&team_players).unwrap();
-    std::fs::write(TEAM_PLAYER_PATH, json).unwrap();
-}
+            if let Some(events_path) = events_output
+            {
+                std::fs::write(events_path, simulation::events_to_csv(&results))
+                    .map_err(|error| Error::Io(events_path.clone(), error))?;
+            }
 
-fn save_matches(matches: Vec<Match>)
-{
-    const MATCHES_PATH: &str = "./data/matches.json";
+            let teams: Vec<Team> = team_players.iter().map(|team| team.team.clone()).collect();
+            let tiebreakers: Vec<Tiebreaker> = tiebreakers.clone().unwrap_or_else(|| table::DEFAULT_TIEBREAKERS.to_vec());
+            let table: LeagueTable = LeagueTable::compute(&teams, &results, &tiebreakers);
+            storage::save_json(&output_or(table_output, "table.json"), &table)?;
+            table.print();
 
-    if Path::new(MATCHES_PATH).exists()
-    {
-        std::fs::remove_file(MATCHES_PATH).unwrap();
+            println!();
+            absence::print_report(&team_players, &season.absences);
+        },
+        Command::SimulateMatch { home, away, events, knockout } =>
+        {
+            println!("Seed: {}", seed);
+
+            let team_players: Vec<TeamWithPlayers> = load_squads(cli, seed)?;
+            let strengths: Vec<TeamStrength> = load_strengths(cli, &team_players)?;
+
+            let team_home: u32 = find_team(&team_players, home)?;
+            let team_out: u32 = find_team(&team_players, away)?;
+
+            let match_seed: u64 = rng::derive_seed(seed, FRIENDLY_STREAM);
+            let game: Match = if *knockout
+            {
+                simulation::play_knockout_match(&team_players, &strengths, &engine, team_home, team_out, (0, 0), match_seed)?
+            }
+            else
+            {
+                simulation::play_match(&team_players, &strengths, &engine, team_home, team_out, match_seed)?
+            };
+
+            if *events
+            {
+                simulation::print_events(&team_players, &game);
+            }
+
+            simulation::print_results(&team_players, std::slice::from_ref(&game));
+            simulation::print_goals(&team_players, &game);
+        },
+        Command::Table { matches, output, tiebreakers } =>
+        {
+            let teams: Vec<Team> = storage::load_teams(&cli.data_dir.join("teams.json"))?;
+            let results: Vec<Match> = storage::load_json(&output_or(matches, "matches.json"))?;
+
+            let tiebreakers: Vec<Tiebreaker> = tiebreakers.clone().unwrap_or_else(|| table::DEFAULT_TIEBREAKERS.to_vec());
+            let table: LeagueTable = LeagueTable::compute(&teams, &results, &tiebreakers);
+            storage::save_json(&output_or(output, "table.json"), &table)?;
+            table.print();
+        },
+        Command::Montecarlo { runs, threads, output, csv_output, tiebreakers } =>
+        {
+            println!("Seed: {}", seed);
+
+            let team_players: Vec<TeamWithPlayers> = load_squads(cli, seed)?;
+            let strengths: Vec<TeamStrength> = load_strengths(cli, &team_players)?;
+
+            let mut config = ProjectionConfig { runs: *runs, engine, ..Default::default() };
+
+            if let Some(threads) = threads
+            {
+                config.threads = *threads;
+            }
+
+            if let Some(tiebreakers) = tiebreakers
+            {
+                config.tiebreakers = tiebreakers.clone();
+            }
+
+            let projection: Projection = montecarlo::project(&team_players, &strengths, seed, &config)?;
+            storage::save_json(&output_or(output, "projection.json"), &projection)?;
+
+            let csv_path: PathBuf = output_or(csv_output, "projection.csv");
+            std::fs::write(&csv_path, projection.to_csv()).map_err(|error| Error::Io(csv_path, error))?;
+            projection.print();
+        },
+        Command::SimulateDivisions { output, next_output, tiebreakers } =>
+        {
+            println!("Seed: {}", seed);
+
+            let team_players: Vec<TeamWithPlayers> = load_squads(cli, seed)?;
+            let strengths: Vec<TeamStrength> = load_strengths(cli, &team_players)?;
+            let pyramid: Pyramid = load_pyramid(cli, &team_players)?;
+            let tiebreakers: Vec<Tiebreaker> = tiebreakers.clone().unwrap_or_else(|| table::DEFAULT_TIEBREAKERS.to_vec());
+
+            let season: PyramidSeason = divisions::play_season(&pyramid, &team_players, &strengths, &engine, &tiebreakers, seed)?;
+            storage::save_json(&output_or(output, "divisions_season.json"), &season)?;
+            storage::save_json(&output_or(next_output, "divisions_next.json"), &season.next)?;
+            season.print(&team_players);
+        },
+        Command::SimulateCup { draw, two_legs, output, bracket_output } =>
+        {
+            println!("Seed: {}", seed);
+
+            let team_players: Vec<TeamWithPlayers> = load_squads(cli, seed)?;
+            let strengths: Vec<TeamStrength> = load_strengths(cli, &team_players)?;
+            let config = CupConfig { draw: *draw, two_legs: *two_legs, engine };
+
+            let cup: Cup = cup::play_cup(&team_players, &strengths, &config, seed)?;
+            storage::save_json(&output_or(output, "cup_matches.json"), &cup.matches())?;
+            storage::save_json(&output_or(bracket_output, "cup_bracket.json"), &cup)?;
+            cup.print(&team_players);
+        },
+        Command::Career { seasons, start, output, tiebreakers } =>
+        {
+            println!("Seed: {}", seed);
+
+            let team_players: Vec<TeamWithPlayers> = load_squads(cli, seed)?;
+            let strengths: Vec<TeamStrength> = load_strengths(cli, &team_players)?;
+
+            let mut config = CareerConfig { seasons: *seasons, start: *start, engine, ..Default::default() };
+
+            if let Some(tiebreakers) = tiebreakers
+            {
+                config.tiebreakers = tiebreakers.clone();
+            }
+
+            let pyramid: Pyramid = load_pyramid(cli, &team_players)?;
+            let career: Career = career::run_career(&team_players, &strengths, &pyramid, &config, seed)?;
+            storage::save_json(&output_or(output, "career.json"), &career)?;
+            career.print();
+        }
     }
 
-    let json: String = serde_json::to_string_pretty(&matches).unwrap();
-    std::fs::write(MATCHES_PATH, json).unwrap();
+    return Ok(());
 }
 
-fn main() 
+fn main()
 {
-    let players: Vec<Player> = load_players();
-    let teams: Vec<Team> = load_teams();
-
-    let team_players: Vec<TeamWithPlayers> = make_teams(players, teams);
-    save_team_players(&team_players);
+    let cli = Cli::parse();
 
-    let standings: Vec<u32> = load_standings();
-    println!("{:?}", standings);
-
-    let results: Vec<Match> = simulation::simulate(&team_players, standings);
-    save_matches(results);
+    if let Err(error) = run(&cli)
+    {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
 }
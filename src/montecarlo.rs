@@ -0,0 +1,220 @@
+use serde::{ Serialize, Deserialize };
+use std::thread;
+
+use crate::config::EngineConfig;
+use crate::entities::{ Team, TeamStrength, TeamWithPlayers };
+use crate::error::Result;
+use crate::table::{ LeagueTable, Tiebreaker };
+use crate::{ rng, simulation };
+
+#[derive(Clone, Debug)]
+pub struct ProjectionConfig
+{
+    pub runs: u32,
+    pub threads: usize,
+    /// Places that count as "top" (Champions League spots and the like).
+    pub top_places: u32,
+    /// Places at the bottom of the table that count as relegation.
+    pub relegation_places: u32,
+    pub tiebreakers: Vec<Tiebreaker>,
+    pub engine: EngineConfig
+}
+
+impl Default for ProjectionConfig
+{
+    fn default() -> Self
+    {
+        ProjectionConfig {
+            runs: 10_000,
+            threads: thread::available_parallelism().map(|count| count.get()).unwrap_or(1),
+            top_places: 4,
+            relegation_places: 3,
+            tiebreakers: crate::table::DEFAULT_TIEBREAKERS.to_vec(),
+            engine: EngineConfig::default()
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct TeamProjection
+{
+    pub team_id: u32,
+    pub team_name: String,
+    /// Probability of finishing in each place, index 0 being champion.
+    pub position_probabilities: Vec<f64>,
+    pub title_probability: f64,
+    pub top_probability: f64,
+    pub relegation_probability: f64,
+    pub expected_points: f64,
+    pub points_p5: u32,
+    pub points_p25: u32,
+    pub points_p50: u32,
+    pub points_p75: u32,
+    pub points_p95: u32
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Projection
+{
+    pub seed: u64,
+    pub runs: u32,
+    pub teams: Vec<TeamProjection>
+}
+
+/// Final (position, points) of every team in one simulated season, in the
+/// order of the `teams` slice.
+type SeasonOutcome = Vec<(u32, u32)>;
+
+/// Simulate the season `config.runs` times spread over `config.threads` worker
+/// threads and summarise where every team ends up. Run `n` always uses a seed
+/// derived from `seed` and `n`, so the projection does not depend on the
+/// thread count.
+pub fn project(teams: &[TeamWithPlayers], strengths: &[TeamStrength], seed: u64, config: &ProjectionConfig) -> Result<Projection>
+{
+    let plain_teams: Vec<Team> = teams.iter().map(|team| team.team.clone()).collect();
+    TeamStrength::validate(strengths, &plain_teams)?;
+
+    let threads = config.threads.max(1) as u32;
+
+    let mut outcomes: Vec<(u32, SeasonOutcome)> = thread::scope(|scope| -> Result<Vec<(u32, SeasonOutcome)>> {
+        let workers: Vec<_> = (0..threads).map(|worker| {
+            let plain_teams = &plain_teams;
+
+            scope.spawn(move || -> Result<Vec<(u32, SeasonOutcome)>> {
+                let mut worker_outcomes: Vec<(u32, SeasonOutcome)> = Vec::new();
+                let mut run = worker;
+
+                while run < config.runs
+                {
+                    let season = simulation::simulate(teams, strengths, &config.engine, rng::derive_seed(seed, run as u64))?;
+                    let table = LeagueTable::compute(plain_teams, &season.matches, &config.tiebreakers);
+
+                    let outcome: SeasonOutcome = plain_teams.iter().map(|team| {
+                        let row = table.rows.iter().find(|row| row.team_id == team.id).unwrap();
+                        (row.position, row.points)
+                    }).collect();
+
+                    worker_outcomes.push((run, outcome));
+                    run += threads;
+                }
+
+                Ok(worker_outcomes)
+            })
+        }).collect();
+
+        let mut outcomes: Vec<(u32, SeasonOutcome)> = Vec::new();
+
+        for worker in workers
+        {
+            outcomes.extend(worker.join().unwrap()?);
+        }
+
+        Ok(outcomes)
+    })?;
+
+    outcomes.sort_by_key(|(run, _)| *run);
+
+    let team_count = plain_teams.len() as u32;
+    let runs = outcomes.len().max(1) as f64;
+    let mut projections: Vec<TeamProjection> = Vec::new();
+
+    for (index, team) in plain_teams.iter().enumerate()
+    {
+        let mut position_counts: Vec<u32> = vec![0; team_count as usize];
+        let mut points: Vec<u32> = Vec::new();
+
+        for (_, outcome) in &outcomes
+        {
+            let (position, team_points) = outcome[index];
+            position_counts[position as usize - 1] += 1;
+            points.push(team_points);
+        }
+
+        points.sort_unstable();
+
+        let share = |from: u32, to: u32| -> f64 {
+            let count: u32 = (from..to.min(team_count)).map(|place| position_counts[place as usize]).sum();
+            count as f64 / runs
+        };
+
+        projections.push(TeamProjection {
+            team_id: team.id,
+            team_name: team.name.clone(),
+            position_probabilities: position_counts.iter().map(|count| *count as f64 / runs).collect(),
+            title_probability: share(0, 1),
+            top_probability: share(0, config.top_places),
+            relegation_probability: share(team_count.saturating_sub(config.relegation_places), team_count),
+            expected_points: points.iter().map(|value| *value as f64).sum::<f64>() / runs,
+            points_p5: percentile(&points, 5),
+            points_p25: percentile(&points, 25),
+            points_p50: percentile(&points, 50),
+            points_p75: percentile(&points, 75),
+            points_p95: percentile(&points, 95)
+        });
+    }
+
+    projections.sort_by(|a, b| b.expected_points.partial_cmp(&a.expected_points).unwrap());
+
+    return Ok(Projection { seed, runs: outcomes.len() as u32, teams: projections });
+}
+
+/// Nearest-rank percentile of already sorted values.
+fn percentile(sorted: &[u32], percent: u32) -> u32
+{
+    if sorted.is_empty()
+    {
+        return 0;
+    }
+
+    let rank = (percent as f64 / 100.0 * sorted.len() as f64).ceil() as usize;
+    return sorted[rank.clamp(1, sorted.len()) - 1];
+}
+
+impl Projection
+{
+    /// One line per team; the position columns hold the probability of
+    /// finishing in that place.
+    pub fn to_csv(&self) -> String
+    {
+        let places = self.teams.first().map(|team| team.position_probabilities.len()).unwrap_or(0);
+        let mut csv = String::from("team_id,team_name,expected_points,title,top,relegation,p5,p25,p50,p75,p95");
+
+        for place in 1..=places
+        {
+            csv += &format!(",pos_{}", place);
+        }
+
+        csv += "\n";
+
+        for team in &self.teams
+        {
+            csv += &format!("{},\"{}\",{:.2},{:.4},{:.4},{:.4},{},{},{},{},{}",
+                team.team_id, team.team_name.replace('"', "\"\""), team.expected_points,
+                team.title_probability, team.top_probability, team.relegation_probability,
+                team.points_p5, team.points_p25, team.points_p50, team.points_p75, team.points_p95);
+
+            for probability in &team.position_probabilities
+            {
+                csv += &format!(",{:.4}", probability);
+            }
+
+            csv += "\n";
+        }
+
+        return csv;
+    }
+
+    pub fn print(&self)
+    {
+        println!("{} seasons simulated (seed {})", self.runs, self.seed);
+        println!("{:<24} {:>7} {:>7} {:>7} {:>7} {:>11}", "Team", "xPts", "Title", "Top", "Releg.", "Pts 5-95%");
+
+        for team in &self.teams
+        {
+            println!("{:<24} {:>7.1} {:>6.1}% {:>6.1}% {:>6.1}% {:>5}-{:<5}",
+                team.team_name, team.expected_points,
+                team.title_probability * 100.0, team.top_probability * 100.0, team.relegation_probability * 100.0,
+                team.points_p5, team.points_p95);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The generator used for every random draw in the simulator. ChaCha is used
+/// instead of `StdRng` because its output is stable across `rand` releases, so
+/// a seed keeps producing the same season.
+pub type SimRng = ChaCha8Rng;
+
+pub fn from_seed(seed: u64) -> SimRng
+{
+    return SimRng::seed_from_u64(seed);
+}
+
+/// Derive an independent sub-seed for `stream` from the master seed (splitmix64).
+/// Used to give every match its own generator, so results don't depend on the
+/// order in which matches are played.
+pub fn derive_seed(master: u64, stream: u64) -> u64
+{
+    let mut z = master.wrapping_add(stream.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    return z ^ (z >> 31);
+}
+
+/// Pick a fresh master seed when none was given on the command line.
+pub fn random_seed() -> u64
+{
+    return rand::random::<u64>();
+}
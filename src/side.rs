@@ -0,0 +1,321 @@
+use crate::entities::{ DEFAULT_STAMINA, Player, Position, TeamWithPlayers };
+use crate::fitness::Fitness;
+use crate::lineup::{ self, Lineup };
+use crate::tactics::TeamSetup;
+
+pub const MAX_SUBSTITUTIONS: u32 = 5;
+pub const MAX_SUBSTITUTION_WINDOWS: u32 = 3;
+/// Extra time brings one more substitution and one more window to make it in.
+const EXTRA_TIME_SUBSTITUTIONS: u32 = 1;
+
+/// Fatigue an outfield player of average stamina builds up per minute, 1.0 is exhausted.
+const FATIGUE_PER_MINUTE: f32 = 0.011;
+/// Keepers barely tire compared to outfield players.
+const GOALKEEPER_FATIGUE: f32 = 0.3;
+/// Fatigue at which a player is taken off when there's a substitute.
+const FATIGUE_LIMIT: f32 = 0.75;
+/// Booked players are taken off sooner, before a tired tackle earns a second yellow.
+const BOOKED_FATIGUE_LIMIT: f32 = 0.5;
+const FIRST_SUBSTITUTION_MINUTE: u32 = 46;
+const CHASING_MINUTE: u32 = 60;
+const PROTECTING_MINUTE: u32 = 75;
+
+/// One team during a match: who is on the pitch, how tired they are, who is
+/// left on the bench and how many changes can still be made.
+#[derive(Clone, Debug)]
+pub struct Side
+{
+    pub team_id: u32,
+    pub setup: TeamSetup,
+    pub on_pitch: Vec<Player>,
+    /// Fatigue of every player in `on_pitch`, at the same index.
+    pub fatigue: Vec<f32>,
+    pub bench: Vec<Player>,
+    /// Fatigue every player in `bench` carries over from earlier matches.
+    bench_fatigue: Vec<f32>,
+    /// Players who left the pitch, substituted, injured or sent off, with
+    /// how tired they were when they went.
+    pub off_pitch: Vec<(u32, f32)>,
+    /// Players on a yellow card.
+    pub booked: Vec<u32>,
+    pub substitutions_left: u32,
+    pub windows_left: u32,
+    /// Whether a change was made this minute. Further changes in the same
+    /// minute go through the same window.
+    window_open: bool,
+    chasing_done: bool,
+    protecting_done: bool
+}
+
+impl Side
+{
+    /// Line the team up for a match, leaving out the `unavailable` players.
+    /// Players start as tired as `fitness` says they still are.
+    pub fn new(team: &TeamWithPlayers, unavailable: &[u32], fitness: &Fitness) -> Side
+    {
+        let Lineup { starters, bench } = lineup::pick_lineup(&team.players, team.setup.formation, unavailable);
+
+        return Side {
+            team_id: team.team.id,
+            setup: team.setup,
+            fatigue: starters.iter().map(|player| fitness.fatigue(player.id)).collect(),
+            on_pitch: starters,
+            bench_fatigue: bench.iter().map(|player| fitness.fatigue(player.id)).collect(),
+            bench,
+            off_pitch: Vec::new(),
+            booked: Vec::new(),
+            substitutions_left: MAX_SUBSTITUTIONS,
+            windows_left: MAX_SUBSTITUTION_WINDOWS,
+            window_open: false,
+            chasing_done: false,
+            protecting_done: false
+        };
+    }
+
+    pub fn player_ids(&self) -> Vec<u32>
+    {
+        return self.on_pitch.iter().map(|player| player.id).collect();
+    }
+
+    /// Rating of the player at `index` in `on_pitch`, worn down by fatigue.
+    /// A fully exhausted player loses `fatigue_effect` of their rating.
+    pub fn effective_rating(&self, index: usize, fatigue_effect: f32) -> f32
+    {
+        return self.on_pitch[index].rating() as f32 * (1.0 - fatigue_effect * self.fatigue[index]);
+    }
+
+    /// Fatigue of everyone who played, the ones taken off included.
+    pub fn fatigue_levels(&self) -> Vec<(u32, f32)>
+    {
+        let mut levels: Vec<(u32, f32)> = self.off_pitch.clone();
+        levels.extend(self.on_pitch.iter().map(|player| player.id).zip(self.fatigue.iter().copied()));
+        return levels;
+    }
+
+    pub fn fatigue_of(&self, player_id: u32) -> f32
+    {
+        return self.fatigue_levels().into_iter()
+            .find(|(id, _)| *id == player_id)
+            .map(|(_, fatigue)| fatigue)
+            .unwrap_or(0.0);
+    }
+
+    /// Let another minute of play wear the players down. Players with less
+    /// stamina tire faster.
+    pub fn tire(&mut self)
+    {
+        for (player, fatigue) in self.on_pitch.iter().zip(self.fatigue.iter_mut())
+        {
+            let mut rate = FATIGUE_PER_MINUTE * (1.5 - player.attributes.stamina as f32 / 100.0) / (1.5 - DEFAULT_STAMINA as f32 / 100.0);
+
+            if player.position == Position::Goalkeeper
+            {
+                rate *= GOALKEEPER_FATIGUE;
+            }
+
+            *fatigue = (*fatigue + rate).min(1.0);
+        }
+    }
+
+    /// Move on to the next minute of play, the next change opens a new window.
+    pub fn start_minute(&mut self)
+    {
+        self.window_open = false;
+    }
+
+    /// Decide on and make this minute's substitutions, returning them as
+    /// (player off, player on) ids. A team chasing the game brings on an
+    /// attacker for a defender, a team protecting a lead does the opposite,
+    /// and exhausted or tiring booked players are replaced. All changes made
+    /// in one minute use up a single window, shared with any changes forced
+    /// by injuries or a keeper sent off in that minute.
+    pub fn substitute(&mut self, minute: u32, goals_for: u32, goals_against: u32) -> Vec<(u32, u32)>
+    {
+        let mut changes: Vec<(u32, u32)> = Vec::new();
+
+        if minute < FIRST_SUBSTITUTION_MINUTE || !self.can_substitute()
+        {
+            return changes;
+        }
+
+        if !self.chasing_done && minute >= CHASING_MINUTE && goals_for < goals_against
+        {
+            self.chasing_done = true;
+
+            if let Some(change) = self.swap_position(&Position::Defender, &Position::Attacker)
+            {
+                changes.push(change);
+            }
+        }
+
+        if !self.protecting_done && minute >= PROTECTING_MINUTE && goals_for > goals_against && self.substitutions_left > 0
+        {
+            self.protecting_done = true;
+
+            if let Some(change) = self.swap_position(&Position::Attacker, &Position::Defender)
+            {
+                changes.push(change);
+            }
+        }
+
+        while self.substitutions_left > 0
+        {
+            let most_tired = self.fatigue.iter().enumerate()
+                .filter(|(index, fatigue)| **fatigue >= FATIGUE_LIMIT || (**fatigue >= BOOKED_FATIGUE_LIMIT && self.booked.contains(&self.on_pitch[*index].id)))
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                .map(|(index, _)| index);
+
+            let replacement = most_tired.and_then(|index| {
+                lineup::best_replacement(&self.bench, &self.on_pitch[index].position).map(|bench_index| (index, bench_index))
+            });
+
+            match replacement
+            {
+                Some((pitch_index, bench_index)) => { changes.push(self.replace(pitch_index, bench_index)); },
+                None => { break; }
+            }
+        }
+
+        if !changes.is_empty()
+        {
+            self.open_window();
+        }
+
+        return changes;
+    }
+
+    /// Allow the change extra time adds on top of the ones left from regular time.
+    pub fn start_extra_time(&mut self)
+    {
+        self.substitutions_left += EXTRA_TIME_SUBSTITUTIONS;
+        self.windows_left += 1;
+    }
+
+    /// The player in goal, or whoever is best at it when the keeper has gone off.
+    pub fn goalkeeper(&self) -> Option<&Player>
+    {
+        return self.on_pitch.iter()
+            .max_by_key(|player| (player.position == Position::Goalkeeper, player.attributes.goalkeeping));
+    }
+
+    /// Players on the pitch at the final whistle ordered for a shootout, the
+    /// best finishers first. Only the first `count` of them take a kick.
+    pub fn penalty_takers(&self, count: usize) -> Vec<&Player>
+    {
+        let mut takers: Vec<&Player> = self.on_pitch.iter().collect();
+        takers.sort_by_key(|player| std::cmp::Reverse(player.attributes.finishing));
+        takers.truncate(count);
+
+        return takers;
+    }
+
+    /// Book the player at `index`, returns true when it's their second yellow.
+    pub fn book(&mut self, index: usize) -> bool
+    {
+        let player_id = self.on_pitch[index].id;
+
+        if self.booked.contains(&player_id)
+        {
+            return true;
+        }
+
+        self.booked.push(player_id);
+        return false;
+    }
+
+    /// Send the player at `index` off, the team plays on a man down. A team
+    /// that loses its keeper puts a substitute keeper in goal for its weakest
+    /// outfield player when it can, returned as (player off, player on).
+    pub fn send_off(&mut self, index: usize) -> Option<(u32, u32)>
+    {
+        let position: Position = self.on_pitch[index].position.clone();
+        self.leave_pitch(index);
+
+        if position != Position::Goalkeeper || !self.can_substitute()
+        {
+            return None;
+        }
+
+        let pitch_index = self.on_pitch.iter().enumerate()
+            .filter(|(_, player)| player.position != Position::Goalkeeper)
+            .min_by_key(|(_, player)| player.rating())
+            .map(|(index, _)| index)?;
+        let bench_index = lineup::best_replacement(&self.bench, &Position::Goalkeeper)?;
+
+        self.open_window();
+        return Some(self.replace(pitch_index, bench_index));
+    }
+
+    /// Take the injured player at `index` off for the best replacement, or
+    /// play on a man down when no change can be made.
+    pub fn injure(&mut self, index: usize) -> Option<(u32, u32)>
+    {
+        if self.can_substitute()
+        {
+            if let Some(bench_index) = lineup::best_replacement(&self.bench, &self.on_pitch[index].position)
+            {
+                self.open_window();
+                return Some(self.replace(index, bench_index));
+            }
+        }
+
+        self.leave_pitch(index);
+        return None;
+    }
+
+    fn can_substitute(&self) -> bool
+    {
+        return self.substitutions_left > 0 && (self.window_open || self.windows_left > 0);
+    }
+
+    /// Use up a window for a change this minute, unless one is already open.
+    fn open_window(&mut self)
+    {
+        if !self.window_open
+        {
+            self.windows_left -= 1;
+            self.window_open = true;
+        }
+    }
+
+    fn leave_pitch(&mut self, index: usize)
+    {
+        let player: Player = self.on_pitch.remove(index);
+        let fatigue: f32 = self.fatigue.remove(index);
+
+        self.off_pitch.push((player.id, fatigue));
+    }
+
+    /// Take the weakest `off` player off for the best `on` player from the bench.
+    fn swap_position(&mut self, off: &Position, on: &Position) -> Option<(u32, u32)>
+    {
+        if self.substitutions_left == 0
+        {
+            return None;
+        }
+
+        let pitch_index = self.on_pitch.iter().enumerate()
+            .filter(|(_, player)| player.position == *off)
+            .min_by_key(|(_, player)| player.rating())
+            .map(|(index, _)| index)?;
+
+        let bench_index = self.bench.iter().enumerate()
+            .filter(|(_, player)| player.position == *on)
+            .max_by_key(|(_, player)| player.rating())
+            .map(|(index, _)| index)?;
+
+        return Some(self.replace(pitch_index, bench_index));
+    }
+
+    fn replace(&mut self, pitch_index: usize, bench_index: usize) -> (u32, u32)
+    {
+        let player_on: Player = self.bench.remove(bench_index);
+        let player_off: Player = std::mem::replace(&mut self.on_pitch[pitch_index], player_on);
+
+        self.off_pitch.push((player_off.id, self.fatigue[pitch_index]));
+        self.fatigue[pitch_index] = self.bench_fatigue.remove(bench_index);
+        self.substitutions_left -= 1;
+
+        return (player_off.id, self.on_pitch[pitch_index].id);
+    }
+}
@@ -1,70 +1,336 @@
 use rand::Rng;
+use rand::distributions::{ Distribution, WeightedIndex };
+use rand::seq::SliceRandom;
+use serde::Serialize;
+
+use crate::absence::{ Absence, Absences };
+use crate::entities::{ Card, CardKind, EventKind, Injury, Match, MatchEvent, Player, PenaltyKick, Position, Goal, Severity, Shootout, Team, TeamStrength, TeamWithPlayers, Zone };
+use crate::config::{ EngineConfig, PositionWeights };
+use crate::error::{ Error, Result };
+use crate::fitness::Fitness;
+use crate::{ entities, rng };
+use crate::side::Side;
+use crate::tactics::{ Phase, TeamSetup };
+
+/// Minutes of regular time.
+pub const MINUTES: u32 = 90;
+/// Minutes of extra time.
+pub const EXTRA_TIME_MINUTES: u32 = 30;
+/// A team left with fewer players on the pitch forfeits the match.
+pub const MIN_PLAYERS: usize = 7;
+
+/// A half of regular or extra time. Kick-offs alternate between the teams.
+struct Half
+{
+    start: u32,
+    length: u32,
+    home_kicks_off: bool
+}
 
-use crate::TeamWithPlayers;
-use crate::{ Match, Player, Position, Goal, Team };
-
-pub fn make_matches(teams: &Vec<TeamWithPlayers>) -> Vec<Match>
+const HALVES: [Half; 2] = [
+    Half { start: 0, length: MINUTES / 2, home_kicks_off: true },
+    Half { start: MINUTES / 2, length: MINUTES / 2, home_kicks_off: false }
+];
+
+const EXTRA_TIME_HALVES: [Half; 2] = [
+    Half { start: MINUTES, length: EXTRA_TIME_MINUTES / 2, home_kicks_off: true },
+    Half { start: MINUTES + EXTRA_TIME_MINUTES / 2, length: EXTRA_TIME_MINUTES / 2, home_kicks_off: false }
+];
+
+/// Build a double round-robin schedule with the circle method. Every team plays
+/// once per matchday (or sits out on a bye when the team count is odd), and the
+/// second half of the season mirrors the first with home and away swapped.
+/// Matches are returned ordered by matchday, starting at 1.
+pub fn make_matches(teams: &[TeamWithPlayers]) -> Vec<Match>
 {
-    let mut matches: Vec<Match> = Vec::new();
+    let mut slots: Vec<Option<u32>> = teams.iter().map(|team| Some(team.team.id)).collect();
 
-    for team_home in teams.clone()
+    //An odd number of teams gets a phantom opponent, playing it means a bye.
+    if slots.len() % 2 == 1
     {
-        let home_team = team_home.clone();
+        slots.push(None);
+    }
 
-        for team_out in teams.clone()
-        {
-            if team_home.team.id == team_out.team.id {
-                break;
-            }
+    if slots.len() < 2
+    {
+        return Vec::new();
+    }
 
-            let out_team = team_out.clone();
-            
-            let game_match: Match = Match {
-                team_home: home_team.clone().team.id,
-                team_out: out_team.clone().team.id,
-                goals: Vec::new()
+    let slot_count = slots.len();
+    let rounds = (slot_count - 1) as u32;
+    let mut first_half: Vec<Match> = Vec::new();
+
+    for round in 0..rounds
+    {
+        for pair in 0..slot_count / 2
+        {
+            let (first, second) = match (slots[pair], slots[slot_count - 1 - pair]) {
+                (Some(first), Some(second)) => (first, second),
+                _ => continue
             };
 
-            matches.push(game_match);
+            //Alternate home and away so nobody plays a long run of either.
+            let swap = if pair == 0 { round % 2 == 1 } else { pair % 2 == 1 };
+            let (team_home, team_out) = if swap { (second, first) } else { (first, second) };
+
+            first_half.push(Match {
+                matchday: round + 1,
+                team_home,
+                team_out,
+                ..Default::default()
+            });
         }
+
+        //Keep the first slot fixed and rotate everyone else one place.
+        let last = slots.pop().unwrap();
+        slots.insert(1, last);
+    }
+
+    let mut matches: Vec<Match> = first_half.clone();
+
+    for game in first_half
+    {
+        matches.push(Match {
+            matchday: game.matchday + rounds,
+            team_home: game.team_out,
+            team_out: game.team_home,
+            ..Default::default()
+        });
     }
     
     return matches;
 }
 
-pub fn simulate(teams: &Vec<TeamWithPlayers>, standings: Vec<u32>) -> Vec<Match>
+/// The played matches of a season and every injury and ban in it.
+#[derive(Clone, Debug, Serialize)]
+pub struct Season
 {
-    let matches_to_play: Vec<Match> = make_matches(&teams);
-    let mut matches_resolved: Vec<Match> = Vec::new();
+    pub matches: Vec<Match>,
+    pub absences: Vec<Absence>
+}
 
+/// Play every match of the season. Each match gets its own generator seeded
+/// from `seed` and its index in the schedule, so the same seed and inputs
+/// always produce the same results. Players carry fatigue from one matchday
+/// into the next and recover in between, and injured or suspended players
+/// miss the matches they're out for. Fails when `strengths` don't cover
+/// exactly the given teams or a squad is empty.
+pub fn simulate(teams: &[TeamWithPlayers], strengths: &[TeamStrength], config: &EngineConfig, seed: u64) -> Result<Season>
+{
+    let plain_teams: Vec<Team> = teams.iter().map(|team| team.team.clone()).collect();
+    TeamStrength::validate(strengths, &plain_teams)?;
 
-    println!("We have {} matches to play.", matches_to_play.len());
+    for team in teams
+    {
+        validate_squad(team)?;
+    }
+
+    let matches_to_play: Vec<Match> = make_matches(teams);
+    let mut matches_resolved: Vec<Match> = Vec::new();
+    let mut fitness: Fitness = Fitness::default();
+    let mut absences: Absences = Absences::default();
+    let mut matchday: u32 = 1;
 
-    for game in matches_to_play
+    for (index, game) in matches_to_play.into_iter().enumerate()
     {
+        if game.matchday != matchday
+        {
+            fitness.recover(teams, (game.matchday - matchday) * config.days_between_matchdays, config.recovery_per_day);
+            matchday = game.matchday;
+        }
+
+        let day: u32 = (game.matchday - 1) * config.days_between_matchdays;
+        let mut home_side: Side = Side::new(&game.get_team(teams, game.team_home).unwrap(), &absences.miss_match(game.team_home, day), &fitness);
+        let mut out_side: Side = Side::new(&game.get_team(teams, game.team_out).unwrap(), &absences.miss_match(game.team_out, day), &fitness);
+
+        let mut match_rng = rng::from_seed(rng::derive_seed(seed, index as u64));
         let game_ref = &mut game.clone();
-        let resolved_match = simulate_match(teams, game_ref, &standings);
+        let resolved_match = simulate_match(game_ref, &mut home_side, &mut out_side, strengths, config, &mut match_rng);
+
+        fitness.record(&home_side);
+        fitness.record(&out_side);
+        absences.record(&resolved_match, day, config);
         matches_resolved.push(resolved_match);
     }
 
-    println!("{:?}", matches_resolved.len());
+    return Ok(Season { matches: matches_resolved, absences: absences.into_report() });
+}
+
+/// Play a single friendly between two teams, outside of any schedule.
+pub fn play_match(teams: &[TeamWithPlayers], strengths: &[TeamStrength], config: &EngineConfig, team_home: u32, team_out: u32, seed: u64) -> Result<Match>
+{
+    validate_pairing(teams, strengths, team_home, team_out)?;
+
+    let mut game = Match { matchday: 0, team_home, team_out, ..Default::default() };
+    let mut home_side: Side = Side::new(&game.get_team(teams, team_home).unwrap(), &[], &Fitness::default());
+    let mut out_side: Side = Side::new(&game.get_team(teams, team_out).unwrap(), &[], &Fitness::default());
+    let mut match_rng = rng::from_seed(seed);
+
+    return Ok(simulate_match(&mut game, &mut home_side, &mut out_side, strengths, config, &mut match_rng));
+}
+
+/// Play a match that must have a winner. When the score, added to the
+/// `earlier` goals of previous legs as (home, out), is level after 90 minutes
+/// the match goes on into two halves of extra time with the same players,
+/// and after that to a penalty shootout. A forfeited match ends there.
+pub fn play_knockout_match(teams: &[TeamWithPlayers], strengths: &[TeamStrength], config: &EngineConfig, team_home: u32, team_out: u32, earlier: (u32, u32), seed: u64) -> Result<Match>
+{
+    validate_pairing(teams, strengths, team_home, team_out)?;
+
+    let mut game = Match { matchday: 0, team_home, team_out, ..Default::default() };
+    let mut home_side: Side = Side::new(&game.get_team(teams, team_home).unwrap(), &[], &Fitness::default());
+    let mut out_side: Side = Side::new(&game.get_team(teams, team_out).unwrap(), &[], &Fitness::default());
+    let mut match_rng = rng::from_seed(seed);
+
+    let is_level = |game: &Match| -> bool {
+        let (home_goals, out_goals) = game.score();
+        return home_goals + earlier.0 == out_goals + earlier.1;
+    };
+
+    simulate_match(&mut game, &mut home_side, &mut out_side, strengths, config, &mut match_rng);
+
+    if game.forfeited_by.is_some()
+    {
+        return Ok(game);
+    }
+
+    if is_level(&game)
+    {
+        game.extra_time = true;
+        home_side.start_extra_time();
+        out_side.start_extra_time();
+
+        for half in &EXTRA_TIME_HALVES
+        {
+            play_half(&mut game, &mut home_side, &mut out_side, strengths, config, half, &mut match_rng);
+        }
+    }
+
+    if game.forfeited_by.is_none() && is_level(&game)
+    {
+        match shootout(&home_side, &out_side, config, &mut match_rng)
+        {
+            Some(shootout) => { game.shootout = Some(shootout); },
+            None => { game.forfeited_by = Some(if home_side.on_pitch.len() < out_side.on_pitch.len() { team_home } else { team_out }); }
+        }
+    }
+
+    return Ok(game);
+}
+
+/// Penalty shootout between the players on the pitch at the end of extra
+/// time. Both teams use as many takers as the one with fewer players left,
+/// best finishers first, and everyone kicks once before anyone kicks again.
+/// After five kicks each, or as soon as one team can't catch up any more,
+/// it goes to sudden death. `None` when a team has nobody left to take or
+/// face a kick.
+fn shootout(home_side: &Side, out_side: &Side, config: &EngineConfig, rng: &mut impl Rng) -> Option<Shootout>
+{
+    const KICKS: u32 = 5;
+
+    let taker_count: usize = home_side.on_pitch.len().min(out_side.on_pitch.len());
+
+    if taker_count == 0
+    {
+        return None;
+    }
+
+    let sides: [(&Side, &Side); 2] = [(home_side, out_side), (out_side, home_side)];
+    let mut goals: [u32; 2] = [0, 0];
+    let mut kicks: Vec<PenaltyKick> = Vec::new();
+    let mut round: u32 = 0;
+
+    'shootout: loop
+    {
+        for (team, (kicking, defending)) in sides.iter().enumerate()
+        {
+            let takers: Vec<&Player> = kicking.penalty_takers(taker_count);
+            let taker: &Player = takers[round as usize % takers.len()];
+            let keeper: &Player = defending.goalkeeper()?;
+
+            let chance: f32 = config.penalty_conversion
+                + (taker.attributes.finishing as f32 - keeper.attributes.goalkeeping as f32) / 200.0
+                - config.fatigue_effect * kicking.fatigue_of(taker.id) / 4.0;
+            let scored: bool = rng.gen::<f32>() < chance.clamp(0.4, 0.95);
+
+            if scored
+            {
+                goals[team] += 1;
+            }
+
+            kicks.push(PenaltyKick { team_id: kicking.team_id, taker_id: taker.id, keeper_id: keeper.id, scored });
+
+            //Within the first five each, stop once the other team can't catch up.
+            if round < KICKS
+            {
+                let home_left: u32 = KICKS - round - 1;
+                let out_left: u32 = KICKS - round - team as u32;
+
+                if goals[0] + home_left < goals[1] || goals[1] + out_left < goals[0]
+                {
+                    break 'shootout;
+                }
+            }
+        }
+
+        round += 1;
+
+        if round >= KICKS && goals[0] != goals[1]
+        {
+            break;
+        }
+    }
+
+    return Some(Shootout { home_goals: goals[0], out_goals: goals[1], kicks });
+}
 
-    return matches_resolved;
+fn validate_pairing(teams: &[TeamWithPlayers], strengths: &[TeamStrength], team_home: u32, team_out: u32) -> Result<()>
+{
+    if team_home == team_out
+    {
+        return Err(Error::Validation(format!("team {} can't play against itself", team_home)));
+    }
+
+    for team_id in [team_home, team_out]
+    {
+        match teams.iter().find(|team| team.team.id == team_id)
+        {
+            Some(team) => { validate_squad(team)?; },
+            None => { return Err(Error::Validation(format!("no squad for team {}", team_id))); }
+        }
+
+        if TeamStrength::find(strengths, team_id).is_none()
+        {
+            return Err(Error::Validation(format!("no strength for team {}", team_id)));
+        }
+    }
+
+    return Ok(());
+}
+
+fn validate_squad(team: &TeamWithPlayers) -> Result<()>
+{
+    if team.players.is_empty()
+    {
+        return Err(Error::Validation(format!("team {} ({}) has no players", team.team.id, team.team.name)));
+    }
+
+    return Ok(());
 }
 
-fn caculate(players: &Vec<Player>, home_min: f32, home_max: f32) -> (u32, u32, u32, u32)
+/// Line strengths of the players on the pitch, with tired players counting
+/// for less than fresh ones.
+fn caculate(side: &Side, fatigue_effect: f32, home_min: f32, home_max: f32, rng: &mut impl Rng) -> (u32, u32, u32, u32)
 {
     let mut sum_atk: u32 = 0;
     let mut sum_mid: u32 = 0;
     let mut sum_def: u32 = 0;
     let mut sum_goal: u32 = 0;
 
-    let mut rng = rand::thread_rng();
-
-    for player in players
+    for (index, player) in side.on_pitch.iter().enumerate()
     {
-        let home_advantage = rng.gen_range(home_min..home_max) as f32;
-        let player_score = (player.quality as f32 * home_advantage).round() as u32;
+        let home_advantage = rng.gen_range(home_min..home_max);
+        let player_score = (side.effective_rating(index, fatigue_effect) * home_advantage).round() as u32;
 
         match player.position
         {
@@ -86,14 +352,272 @@ fn caculate(players: &Vec<Player>, home_min: f32, home_max: f32) -> (u32, u32, u
     return (sum_atk, sum_mid, sum_def, sum_goal);
 }
 
-fn simulate_match(teams: &Vec<TeamWithPlayers>, game_match: &mut Match, standings: &Vec<u32>) -> Match
+/// Scale the line strengths from `caculate` by the team's tactics.
+fn apply_setup(scores: (u32, u32, u32, u32), setup: &TeamSetup) -> (u32, u32, u32, u32)
+{
+    let (sum_atk, sum_mid, sum_def, sum_goal) = scores;
+    let (atk_factor, mid_factor, def_factor) = setup.line_factors();
+
+    return (
+        (sum_atk as f32 * atk_factor).round() as u32,
+        (sum_mid as f32 * mid_factor).round() as u32,
+        (sum_def as f32 * def_factor).round() as u32,
+        sum_goal
+    );
+}
+
+/// How far the ball moves when a team wins `phase`, given its tactics.
+fn advance(delta: i32, setup: &TeamSetup, phase: Phase) -> i32
+{
+    return (delta as f32 * setup.tactics.advance_factor(phase)).round() as i32;
+}
+
+/// Pick who takes a shot, weighted by position and finishing. `None` when
+/// nobody is left on the pitch to take it.
+fn pick_shooter(side: &Side, config: &EngineConfig, rng: &mut impl Rng) -> Option<Player>
+{
+    return pick_weighted(&side.on_pitch, &config.scorer_weights, |player| player.attributes.finishing, None, rng);
+}
+
+/// How much a shooter's finishing adds to (or takes off) the shot roll.
+fn finishing_bonus(shooter: &Player) -> i32
+{
+    const FINISHING_BASELINE: f32 = 70.0;
+    const FINISHING_EFFECT: f32 = 0.3;
+
+    return ((shooter.attributes.finishing as f32 - FINISHING_BASELINE) * FINISHING_EFFECT).round() as i32;
+}
+
+/// Credit a goal to the `scorer` and maybe to an assisting teammate, weighted
+/// by position and passing.
+fn credit_goal(side: &Side, scorer: &Player, config: &EngineConfig, minute: u32, rng: &mut impl Rng) -> Goal
+{
+    let mut assist_player_id: Option<u32> = None;
+
+    if rng.gen_bool(config.assist_chance.clamp(0.0, 1.0) as f64)
+    {
+        assist_player_id = pick_weighted(&side.on_pitch, &config.assist_weights, |player| player.attributes.passing, Some(scorer.id), rng).map(|player| player.id);
+    }
+
+    return Goal { time: minute, team_id: side.team_id, player_id: scorer.id, assist_player_id, added_time: 0 };
+}
+
+/// Draw an index with chance proportional to `weights`, or an even draw when
+/// every weight is zero.
+fn pick_index(weights: &[f32], rng: &mut impl Rng) -> Option<usize>
+{
+    if weights.is_empty()
+    {
+        return None;
+    }
+
+    if let Ok(distribution) = WeightedIndex::new(weights)
+    {
+        return Some(distribution.sample(rng));
+    }
+
+    return Some(rng.gen_range(0..weights.len()));
+}
+
+/// Draw a player with chance proportional to their position weight times
+/// their `attribute`, never picking `exclude`. Falls back to an even draw when
+/// every weight is zero, and only returns `None` when nobody is left.
+fn pick_weighted(players: &[Player], weights: &PositionWeights, attribute: fn(&Player) -> u8, exclude: Option<u32>, rng: &mut impl Rng) -> Option<Player>
+{
+    let candidates: Vec<&Player> = players.iter()
+        .filter(|player| Some(player.id) != exclude)
+        .collect();
+
+    let player_weights: Vec<f32> = candidates.iter()
+        .map(|player| weights.weight(&player.position) * attribute(player) as f32)
+        .collect();
+
+    if let Ok(distribution) = WeightedIndex::new(&player_weights)
+    {
+        return Some(candidates[distribution.sample(rng)].clone());
+    }
+
+    return candidates.choose(rng).map(|player| (*player).clone());
+}
+
+/// Zone of the pitch the ball is in.
+fn zone_of(field: i32) -> Zone
+{
+    match field
+    {
+        i32::MIN..=0 => Zone::HomeGoal,
+        1..=24 => Zone::HomeHalf,
+        25..=74 => Zone::Midfield,
+        75..=99 => Zone::OutHalf,
+        _ => Zone::OutGoal
+    }
+}
+
+/// Make the substitutions `side` wants this minute and log them.
+fn make_substitutions(game_match: &mut Match, side: &mut Side, minute: u32, goals_for: u32, goals_against: u32, field: i32)
+{
+    for change in side.substitute(minute, goals_for, goals_against)
+    {
+        log_substitution(game_match, side, minute, field, change);
+    }
+}
+
+fn log_substitution(game_match: &mut Match, side: &Side, minute: u32, field: i32, (player_off, player_on): (u32, u32))
 {
+    game_match.events.push(MatchEvent {
+        minute,
+        zone: zone_of(field),
+        team_id: side.team_id,
+        kind: EventKind::Substitution,
+        player_ids: vec![player_off, player_on],
+        field,
+        fatigue: vec![side.fatigue_of(player_off), side.fatigue_of(player_on)],
+        added_time: 0
+    });
+}
+
+/// Roll for cards and injuries on `side` this minute, record them and make
+/// the changes they force.
+fn roll_incidents(game_match: &mut Match, side: &mut Side, config: &EngineConfig, minute: u32, field: i32, rng: &mut impl Rng)
+{
+    let card_weights: Vec<f32> = side.on_pitch.iter().map(|player| config.card_weights.weight(&player.position)).collect();
+
+    if rng.gen_bool(config.yellow_card_chance.clamp(0.0, 1.0) as f64)
+    {
+        if let Some(index) = pick_index(&card_weights, rng)
+        {
+            let kind: CardKind = if side.book(index) { CardKind::SecondYellow } else { CardKind::Yellow };
+            give_card(game_match, side, index, kind, minute, field);
+        }
+    }
+
+    let card_weights: Vec<f32> = side.on_pitch.iter().map(|player| config.card_weights.weight(&player.position)).collect();
+
+    if rng.gen_bool(config.red_card_chance.clamp(0.0, 1.0) as f64)
+    {
+        if let Some(index) = pick_index(&card_weights, rng)
+        {
+            give_card(game_match, side, index, CardKind::Red, minute, field);
+        }
+    }
+
+    //Tired players get injured more often, up to twice as often when exhausted.
+    let injury_weights: Vec<f32> = side.fatigue.iter().map(|fatigue| 1.0 + fatigue).collect();
+    let average_weight: f32 = injury_weights.iter().sum::<f32>() / injury_weights.len().max(1) as f32;
+
+    if rng.gen_bool((config.injury_chance * average_weight).clamp(0.0, 1.0) as f64)
+    {
+        if let Some(index) = pick_index(&injury_weights, rng)
+        {
+            let (severity, days_out) = injury_severity(rng);
+            let player_id: u32 = side.on_pitch[index].id;
+
+            game_match.injuries.push(Injury { time: minute, team_id: side.team_id, player_id, severity, days_out, added_time: 0 });
+            game_match.add_event(minute, zone_of(field), side.team_id, EventKind::Injury, vec![player_id], field);
+
+            if let Some(change) = side.injure(index)
+            {
+                log_substitution(game_match, side, minute, field, change);
+            }
+        }
+    }
+}
+
+fn give_card(game_match: &mut Match, side: &mut Side, index: usize, kind: CardKind, minute: u32, field: i32)
+{
+    let card = Card { time: minute, team_id: side.team_id, player_id: side.on_pitch[index].id, kind, added_time: 0 };
+    let event_kind: EventKind = if card.is_sending_off() { EventKind::RedCard } else { EventKind::YellowCard };
+
+    game_match.add_event(minute, zone_of(field), side.team_id, event_kind, vec![card.player_id], field);
+
+    if card.is_sending_off()
+    {
+        if let Some(change) = side.send_off(index)
+        {
+            log_substitution(game_match, side, minute, field, change);
+        }
+    }
+
+    game_match.cards.push(card);
+}
+
+/// How bad an injury is and how many days it keeps the player out.
+fn injury_severity(rng: &mut impl Rng) -> (Severity, u32)
+{
+    let roll: f32 = rng.gen();
+
+    if roll < 0.6
+    {
+        return (Severity::Minor, rng.gen_range(3..=10));
+    }
+    else if roll < 0.9
+    {
+        return (Severity::Moderate, rng.gen_range(11..=35));
+    }
+
+    return (Severity::Serious, rng.gen_range(36..=180));
+}
+
+/// Log how tired everyone on the pitch for `side` is.
+fn log_fatigue(game_match: &mut Match, side: &Side, minute: u32, field: i32)
+{
+    game_match.events.push(MatchEvent {
+        minute,
+        zone: zone_of(field),
+        team_id: side.team_id,
+        kind: EventKind::Fatigue,
+        player_ids: side.player_ids(),
+        field,
+        fatigue: side.fatigue.clone(),
+        added_time: 0
+    });
+}
+
+fn goal_player_ids(goal: &Goal) -> Vec<u32>
+{
+    let mut ids: Vec<u32> = vec![goal.player_id];
+    ids.extend(goal.assist_player_id);
+    return ids;
+}
+
+fn goalkeeper_ids(players: &[Player]) -> Vec<u32>
+{
+    return players.iter()
+        .filter(|player| player.position == Position::Goalkeeper)
+        .map(|player| player.id)
+        .take(1)
+        .collect();
+}
+
+fn simulate_match(game_match: &mut Match, home_side: &mut Side, out_side: &mut Side, strengths: &[TeamStrength], config: &EngineConfig, rng: &mut impl Rng) -> Match
+{
+    game_match.home_lineup = home_side.player_ids();
+    game_match.out_lineup = out_side.player_ids();
+
+    for half in &HALVES
+    {
+        play_half(game_match, home_side, out_side, strengths, config, half, rng);
+    }
+
+    return game_match.clone();
+}
+
+/// Play a half, kicking off from the centre and carrying on from the score
+/// so far. Once its minutes are up, stoppage time is added for what happened
+/// in the half. The match is abandoned as soon as a team has fewer than
+/// `MIN_PLAYERS` left, and nothing more is played after that.
+fn play_half(game_match: &mut Match, home_side: &mut Side, out_side: &mut Side, strengths: &[TeamStrength], config: &EngineConfig, half: &Half, rng: &mut impl Rng)
+{
+    if game_match.forfeited_by.is_some()
+    {
+        return;
+    }
+
     /*
         The socccer field is layed out as following:
         0 = home_goal, 25 = home_mid, 50 = center, 75 = out mid, 100 = out goal
     */
 
-    const MINUTES: u32 = 90;
     const STANDING_FACTOR_MIN: f32 = 1.2;
     const STANDING_FACTOR_MAX: f32 = 1.4;
     const HOME_FACTOR_MIN: f32 = 1.05;
@@ -101,35 +625,73 @@ fn simulate_match(teams: &Vec<TeamWithPlayers>, game_match: &mut Match, standing
     const HOME_PITCH: i32 = 45;
     const OUT_PITCH: i32 = 55;
     const GOAL_REBOUND: i32 = 33;
+    const FATIGUE_LOG_INTERVAL: u32 = 15;
+
+    //The team kicking off starts with the ball on its own side of the centre.
+    let mut field: i32 = if half.home_kicks_off { HOME_PITCH } else { OUT_PITCH };
 
-    //49 because home gets to kick the ball off the center always.
-    let mut field: i32 = HOME_PITCH;
-    let mut rng = rand::thread_rng();
+    let (mut goals_home, mut goals_out) = game_match.score();
 
-    let mut goals_out = 0;
-    let mut goals_home = 0;
+    let home_strength = TeamStrength::find(strengths, game_match.team_home).expect("No strength for home team").effective_rating();
+    let out_strength = TeamStrength::find(strengths, game_match.team_out).expect("No strength for out team").effective_rating();
 
-    for minute in 0 .. MINUTES
+    let team_home = game_match.team_home;
+    let team_out = game_match.team_out;
+    let home_setup: TeamSetup = home_side.setup;
+    let out_setup: TeamSetup = out_side.setup;
+
+    let half_end: u32 = half.start + half.length;
+    let kick_off_team: u32 = if half.home_kicks_off { team_home } else { team_out };
+    let first_event: usize = game_match.events.len();
+    let mut stoppage: u32 = 0;
+    let mut played: u32 = 0;
+
+    game_match.add_event(half.start, Zone::Midfield, kick_off_team, EventKind::KickOff, Vec::new(), field);
+
+    while played < half.length + stoppage
     {
-        let standing_advantage = rng.gen_range(STANDING_FACTOR_MIN..STANDING_FACTOR_MAX) as f32;
-        let home_team_standing = (*standings.get(game_match.team_home as usize - 1).unwrap() as f32 * standing_advantage).floor() as u32;
-        let out_team_standing = (*standings.get(game_match.team_out as usize - 1).unwrap() as f32 * standing_advantage).floor() as u32;
+        //Stoppage time is played at the minute the half ends on.
+        let (minute, added_time) = if played < half.length { (half.start + played, 0) } else { (half_end, played - half.length + 1) };
+        let mark: Mark = Mark::new(game_match);
+
+        let standing_advantage = rng.gen_range(STANDING_FACTOR_MIN..STANDING_FACTOR_MAX);
+        let home_team_standing = (home_strength * standing_advantage).floor() as u32;
+        let out_team_standing = (out_strength * standing_advantage).floor() as u32;
+
+        if minute > half.start && added_time == 0 && minute % FATIGUE_LOG_INTERVAL == 0
+        {
+            log_fatigue(game_match, home_side, minute, field);
+            log_fatigue(game_match, out_side, minute, field);
+        }
+
+        home_side.start_minute();
+        out_side.start_minute();
+        make_substitutions(game_match, home_side, minute, goals_home, goals_out, field);
+        make_substitutions(game_match, out_side, minute, goals_out, goals_home, field);
+        roll_incidents(game_match, home_side, config, minute, field, rng);
+        roll_incidents(game_match, out_side, config, minute, field, rng);
 
-        let (home_players, out_players) = &game_match.get_players(teams);
+        if let Some(short_side) = [&*home_side, &*out_side].iter().find(|side| side.on_pitch.len() < MIN_PLAYERS)
+        {
+            game_match.forfeited_by = Some(short_side.team_id);
+            game_match.add_event(minute, zone_of(field), short_side.team_id, EventKind::Abandoned, Vec::new(), field);
+            mark.set_added_time(game_match, added_time);
+            break;
+        }
 
-        let (home_atk, home_mid, home_def, home_goal) = caculate(home_players, HOME_FACTOR_MIN, HOME_FACTOR_MAX);
-        let (out_atk, out_mid, out_def, out_goal) = caculate(out_players, HOME_FACTOR_MIN, HOME_FACTOR_MAX);
+        let (home_atk, home_mid, home_def, home_goal) = apply_setup(caculate(home_side, config.fatigue_effect, HOME_FACTOR_MIN, HOME_FACTOR_MAX, rng), &home_setup);
+        let (out_atk, out_mid, out_def, out_goal) = apply_setup(caculate(out_side, config.fatigue_effect, HOME_FACTOR_MIN, HOME_FACTOR_MAX, rng), &out_setup);
 
        // println!("{} | {} {} {} {} vs {} {} {} {}", field, home_atk, home_def, home_mid, home_goal, out_atk, out_def, out_mid, out_goal);
         
         let home_variance_upper = home_team_standing as f32 / 1000.0;
         let out_variance_upper = out_team_standing as f32 / 1000.0;
 
-        let home_variance = rng.gen_range(0.98..1.0 + home_variance_upper) as f32;
-        let out_variance = rng.gen_range(0.98..1.0 + out_variance_upper) as f32;
+        let home_variance = rng.gen_range(0.98..1.0 + home_variance_upper);
+        let out_variance = rng.gen_range(0.98..1.0 + out_variance_upper);
 
         //Handle logic if home team is attacking.
-        if field >= 75 && field < 100
+        if (75..100).contains(&field)
         {
             let mut home_attack_score = (home_atk as f32+ home_mid as f32 * 0.33).round() as i32;
             home_attack_score = (home_attack_score as f32 * home_variance).round() as i32;
@@ -137,31 +699,35 @@ fn simulate_match(teams: &Vec<TeamWithPlayers>, game_match: &mut Match, standing
             let mut out_defending_score = (out_def as f32 + out_mid as f32 * 0.33).round() as i32;
             out_defending_score = (out_defending_score as f32 * out_variance).round() as i32;
 
-            let delta = (home_attack_score as i32 - out_defending_score as i32).abs();
+            let delta = (home_attack_score - out_defending_score).abs();
 
             if out_defending_score > home_attack_score 
             {
-                field -= delta
+                field -= advance(delta, &out_setup, Phase::Repel);
+                game_match.add_event(minute, Zone::OutHalf, team_out, EventKind::AttackRepelled, Vec::new(), field);
             }
             else 
             {
-                field += delta;
+                field += advance(delta, &home_setup, Phase::Attack);
+                game_match.add_event(minute, Zone::OutHalf, team_home, EventKind::AttackAdvanced, Vec::new(), field);
             }
         }
         //Handle logic when both team are in the mid field.
-        else if field < 75 && field >= 25
+        else if (25..75).contains(&field)
         {
             let home_mid_score = (home_mid as f32 * home_variance).round() as i32;
             let out_mid_score = (out_mid as f32 * out_variance).round() as i32;
-            let delta = (home_mid_score as i32 - out_mid_score as i32).abs();
+            let delta = (home_mid_score - out_mid_score).abs();
 
             if out_mid_score > home_mid_score 
             {
-                field -= delta;
+                field -= advance(delta, &out_setup, Phase::Midfield);
+                game_match.add_event(minute, Zone::Midfield, team_out, EventKind::MidfieldWon, Vec::new(), field);
             }
             else 
             {
-                field += delta;
+                field += advance(delta, &home_setup, Phase::Midfield);
+                game_match.add_event(minute, Zone::Midfield, team_home, EventKind::MidfieldWon, Vec::new(), field);
             }
         }
         //Handle logic when out is attacking
@@ -173,72 +739,217 @@ fn simulate_match(teams: &Vec<TeamWithPlayers>, game_match: &mut Match, standing
             let mut home_defending_score = (home_def as f32 + home_mid as f32 * 0.33).round() as i32;
             home_defending_score = (home_defending_score as f32 * home_variance).round() as i32;
 
-            let delta = (home_defending_score as i32 - out_attack_score as i32).abs() / 10;
+            let delta = (home_defending_score - out_attack_score).abs() / 10;
 
             if home_defending_score > out_attack_score 
             {
-                field += delta
+                field += advance(delta, &home_setup, Phase::Repel);
+                game_match.add_event(minute, Zone::HomeHalf, team_home, EventKind::AttackRepelled, Vec::new(), field);
             }
             else 
             {
-                field -= delta;
+                field -= advance(delta, &out_setup, Phase::Attack);
+                game_match.add_event(minute, Zone::HomeHalf, team_out, EventKind::AttackAdvanced, Vec::new(), field);
             }
         }
         else if field >= 100 || field <= 0
         {
-            let goal_pick = rng.gen_range(0..100) as u32;
-            let goal_variance = rng.gen_range(0.9..1.11) as f32;
-            
-            let home_goal_score = (home_goal as f32 * goal_variance).round() as u32 + (goals_home * 12);
-            let out_goal_score = (out_goal as f32 * goal_variance).round() as u32 + (goals_out * 12);
+            let goal_pick: i32 = rng.gen_range(0..100);
+            let goal_variance = rng.gen_range(0.9..1.11);
 
             if field > 100 
             {
-                //println!("HOME {} | {} > {}", field, goal_pick, home_goal_score);
-                if goal_pick > home_goal_score
+                let save_score = (out_goal as f32 * goal_variance).round() as i32 + (goals_home * 12) as i32;
+                let shooter: Option<Player> = pick_shooter(home_side, config, rng)
+                    .filter(|shooter| goal_pick + finishing_bonus(shooter) > save_score);
+
+                if let Some(shooter) = shooter
                 {
-                   // println!("HOME GOAL {} {}", goals_out, goals_out * 5);
-                    let team_copy: TeamWithPlayers = game_match.get_team(teams, game_match.team_home).unwrap();
-                    let scorerer: Player = team_copy.get_random_player(Position::Attacker);
-                    let home_goal = Goal { time: minute, team_id: team_copy.team.id, player_id: scorerer.id };
+                    let home_goal: Goal = credit_goal(home_side, &shooter, config, minute, rng);
+                    let scorer_ids: Vec<u32> = goal_player_ids(&home_goal);
                     game_match.add_goal(home_goal);
 
                     goals_home += 1;
                     field = OUT_PITCH;
+                    game_match.add_event(minute, Zone::OutGoal, team_home, EventKind::Goal, scorer_ids, field);
+                    game_match.add_event(minute, Zone::Midfield, team_out, EventKind::KickOff, Vec::new(), field);
                 }
                 else
                 {
                     field = 100 - GOAL_REBOUND;
+                    game_match.add_event(minute, Zone::OutGoal, team_out, EventKind::Save, goalkeeper_ids(&out_side.on_pitch), field);
                 }
             }
             else
             {
-                //println!("OUT {} | {} > {}", field, goal_pick, out_goal_score);
+                let save_score = (home_goal as f32 * goal_variance).round() as i32 + (goals_out * 12) as i32;
+                let shooter: Option<Player> = pick_shooter(out_side, config, rng)
+                    .filter(|shooter| goal_pick + finishing_bonus(shooter) > save_score);
 
-                if goal_pick > out_goal_score 
+                if let Some(shooter) = shooter
                 {
-                    //println!("OUT GOAL {} {}", goals_home, goals_home * 5);
-                    let team_copy: TeamWithPlayers = game_match.get_team(teams, game_match.team_out).unwrap();
-                    let scorerer: Player = team_copy.get_random_player(Position::Attacker);
-                    let out_goal = Goal { time: minute, team_id: team_copy.team.id, player_id: scorerer.id };
+                    let out_goal: Goal = credit_goal(out_side, &shooter, config, minute, rng);
+                    let scorer_ids: Vec<u32> = goal_player_ids(&out_goal);
                     game_match.add_goal(out_goal);
 
                     goals_out += 1;
                     field = HOME_PITCH;
+                    game_match.add_event(minute, Zone::HomeGoal, team_out, EventKind::Goal, scorer_ids, field);
+                    game_match.add_event(minute, Zone::Midfield, team_home, EventKind::KickOff, Vec::new(), field);
                 }
                 else
                 {
-                    field = 0 + GOAL_REBOUND;
+                    field = GOAL_REBOUND;
+                    game_match.add_event(minute, Zone::HomeGoal, team_home, EventKind::Save, goalkeeper_ids(&home_side.on_pitch), field);
                 }
             }
         }
+
+        home_side.tire();
+        out_side.tire();
+        mark.set_added_time(game_match, added_time);
+        played += 1;
+
+        if played == half.length
+        {
+            stoppage = stoppage_time(&game_match.events[first_event..]);
+            game_match.stoppage.push(stoppage);
+        }
     }
 
-    println!("{} vs. {} {} - {} ", 
-        game_match.get_team(teams, game_match.team_home).unwrap().team.name,
-        game_match.get_team(teams, game_match.team_out).unwrap().team.name,
-        goals_home, goals_out
-    );
+    let mark: Mark = Mark::new(game_match);
+    log_fatigue(game_match, home_side, half_end, field);
+    log_fatigue(game_match, out_side, half_end, field);
+    mark.set_added_time(game_match, stoppage);
+}
 
-    return game_match.clone();
+/// Stoppage time at the end of a half: a minute to start with, half a minute
+/// for every goal, a third for every substitution and a minute per injury.
+fn stoppage_time(events: &[MatchEvent]) -> u32
+{
+    const MAX_STOPPAGE: u32 = 10;
+
+    let count = |kind: EventKind| -> f32 {
+        return events.iter().filter(|event| event.kind == kind).count() as f32;
+    };
+
+    let minutes: f32 = 1.0 + count(EventKind::Goal) / 2.0 + count(EventKind::Substitution) / 3.0 + count(EventKind::Injury);
+
+    return (minutes.round() as u32).min(MAX_STOPPAGE);
+}
+
+/// How many goals, cards, injuries and events a match had at some point, so
+/// whatever happened since can be put in stoppage time.
+struct Mark
+{
+    goals: usize,
+    cards: usize,
+    injuries: usize,
+    events: usize
+}
+
+impl Mark
+{
+    fn new(game_match: &Match) -> Mark
+    {
+        return Mark { goals: game_match.goals.len(), cards: game_match.cards.len(), injuries: game_match.injuries.len(), events: game_match.events.len() };
+    }
+
+    fn set_added_time(&self, game_match: &mut Match, added_time: u32)
+    {
+        game_match.goals[self.goals..].iter_mut().for_each(|goal| goal.added_time = added_time);
+        game_match.cards[self.cards..].iter_mut().for_each(|card| card.added_time = added_time);
+        game_match.injuries[self.injuries..].iter_mut().for_each(|injury| injury.added_time = added_time);
+        game_match.events[self.events..].iter_mut().for_each(|event| event.added_time = added_time);
+    }
+}
+
+pub fn print_results(teams: &[TeamWithPlayers], matches: &[Match])
+{
+    println!("We played {} matches.", matches.len());
+
+    for game in matches
+    {
+        let (goals_home, goals_out) = game.score();
+
+        let ending: String = match &game.shootout
+        {
+            Some(shootout) => format!("a.e.t., {} - {} on penalties", shootout.home_goals, shootout.out_goals),
+            None if game.forfeited_by.is_some() => "abandoned, awarded".to_owned(),
+            None if game.extra_time => "a.e.t.".to_owned(),
+            None => String::new()
+        };
+
+        println!("{} vs. {} {} - {} {}", 
+            game.get_team(teams, game.team_home).unwrap().team.name,
+            game.get_team(teams, game.team_out).unwrap().team.name,
+            goals_home, goals_out, ending
+        );
+    }
+}
+
+/// Name of a player of any team, empty for an unknown id.
+fn player_name(teams: &[TeamWithPlayers], player_id: u32) -> String
+{
+    return teams.iter()
+        .flat_map(|team| team.players.iter())
+        .find(|player| player.id == player_id)
+        .map(|player| player.name.clone())
+        .unwrap_or_default();
+}
+
+/// Print the goals of a match with their scorers, assists and minutes.
+pub fn print_goals(teams: &[TeamWithPlayers], game: &Match)
+{
+    for goal in &game.goals
+    {
+        let assist: String = goal.assist_player_id.map(|player_id| format!(" (assist {})", player_name(teams, player_id))).unwrap_or_default();
+
+        println!("{:>6}' {}{}", entities::match_minute(goal.time, goal.added_time), player_name(teams, goal.player_id), assist);
+    }
+}
+
+/// Every event of every match as CSV, one line per event.
+pub fn events_to_csv(matches: &[Match]) -> String
+{
+    let mut csv = String::from("matchday,team_home,team_out,minute,zone,team_id,kind,player_ids,field,fatigue,added_time\n");
+
+    for game in matches
+    {
+        for event in &game.events
+        {
+            let player_ids: Vec<String> = event.player_ids.iter().map(|id| id.to_string()).collect();
+            let fatigue: Vec<String> = event.fatigue.iter().map(|fatigue| format!("{:.2}", fatigue)).collect();
+
+            csv += &format!("{},{},{},{},{:?},{},{:?},{},{},{},{}\n",
+                game.matchday, game.team_home, game.team_out, event.minute, event.zone,
+                event.team_id, event.kind, player_ids.join(" "), event.field, fatigue.join(" "), event.added_time);
+        }
+    }
+
+    return csv;
+}
+
+pub fn print_events(teams: &[TeamWithPlayers], game: &Match)
+{
+    for event in &game.events
+    {
+        let team_name = game.get_team(teams, event.team_id).map(|team| team.team.name).unwrap_or_default();
+
+        let fatigue: Vec<String> = event.fatigue.iter().map(|fatigue| format!("{:.0}%", fatigue * 100.0)).collect();
+
+        println!("{:>6}' {:<9} {:<15} {:<24} {:>4} {:?} {}",
+            entities::match_minute(event.minute, event.added_time), format!("{:?}", event.zone), format!("{:?}", event.kind), team_name, event.field, event.player_ids, fatigue.join(" "));
+    }
+
+    if let Some(shootout) = &game.shootout
+    {
+        for kick in &shootout.kicks
+        {
+            let team_name = game.get_team(teams, kick.team_id).map(|team| team.team.name).unwrap_or_default();
+            let result: &str = if kick.scored { "scores" } else { "misses" };
+
+            println!("Pen. {:<24} {} {} against {}", team_name, player_name(teams, kick.taker_id), result, player_name(teams, kick.keeper_id));
+        }
+    }
 }
@@ -0,0 +1,171 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{ Serialize, Deserialize };
+
+use crate::entities::{ Player, Position, Team, TeamWithPlayers };
+use crate::error::{ Error, Result };
+
+/// Smallest and largest squad a team may register.
+pub const MIN_SQUAD_SIZE: u32 = 18;
+pub const MAX_SQUAD_SIZE: u32 = 25;
+
+const POSITIONS: [Position; 4] = [Position::Attacker, Position::Midfield, Position::Defender, Position::Goalkeeper];
+
+/// How many players of each position go into a squad.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SquadConfig
+{
+    pub attackers: u32,
+    pub midfielders: u32,
+    pub defenders: u32,
+    pub goalkeepers: u32
+}
+
+impl Default for SquadConfig
+{
+    fn default() -> Self
+    {
+        SquadConfig { attackers: 5, midfielders: 6, defenders: 7, goalkeepers: 2 }
+    }
+}
+
+impl SquadConfig
+{
+    pub fn count(&self, position: &Position) -> u32
+    {
+        match position
+        {
+            Position::Attacker => self.attackers,
+            Position::Midfield => self.midfielders,
+            Position::Defender => self.defenders,
+            Position::Goalkeeper => self.goalkeepers,
+            Position::NotOnTheField => 0
+        }
+    }
+
+    pub fn size(&self) -> u32
+    {
+        return self.attackers + self.midfielders + self.defenders + self.goalkeepers;
+    }
+
+    /// Squads must be 18 to 25 players with at least one keeper, so a full
+    /// eleven plus a bench can always be named.
+    pub fn validate(&self) -> Result<()>
+    {
+        if !(MIN_SQUAD_SIZE..=MAX_SQUAD_SIZE).contains(&self.size())
+        {
+            return Err(Error::Validation(format!(
+                "squads must have {} to {} players, got {}", MIN_SQUAD_SIZE, MAX_SQUAD_SIZE, self.size()
+            )));
+        }
+
+        if self.goalkeepers == 0
+        {
+            return Err(Error::Validation("squads need at least one goalkeeper".to_owned()));
+        }
+
+        return Ok(());
+    }
+}
+
+/// Where squad members come from.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SquadSource
+{
+    /// Deal shuffled players out over the teams, nobody plays for two teams.
+    Random,
+    /// Take the best players whose crawled `club` matches the team name.
+    RealClubs
+}
+
+/// Build a squad for every team. Fails up front when the player pool can't
+/// fill the requested squad sizes, instead of searching forever.
+pub fn build_squads(players: &[Player], teams: &[Team], config: &SquadConfig, source: SquadSource, rng: &mut impl Rng) -> Result<Vec<TeamWithPlayers>>
+{
+    config.validate()?;
+
+    match source
+    {
+        SquadSource::Random => build_random(players, teams, config, rng),
+        SquadSource::RealClubs => build_real_clubs(players, teams, config)
+    }
+}
+
+fn build_random(players: &[Player], teams: &[Team], config: &SquadConfig, rng: &mut impl Rng) -> Result<Vec<TeamWithPlayers>>
+{
+    let mut teams_with_players: Vec<TeamWithPlayers> = teams.iter()
+        .map(|team| TeamWithPlayers { team: team.clone(), ..Default::default() })
+        .collect();
+
+    for position in POSITIONS.iter()
+    {
+        let mut pool: Vec<&Player> = players.iter().filter(|player| player.position == *position).collect();
+        let needed = config.count(position) as usize * teams.len();
+
+        if pool.len() < needed
+        {
+            return Err(Error::Validation(format!(
+                "{} teams need {} {:?} players but only {} are available",
+                teams.len(), needed, position, pool.len()
+            )));
+        }
+
+        pool.shuffle(rng);
+
+        for (index, player) in pool.into_iter().take(needed).enumerate()
+        {
+            teams_with_players[index % teams.len()].players.push(player.clone());
+        }
+    }
+
+    return Ok(teams_with_players);
+}
+
+fn build_real_clubs(players: &[Player], teams: &[Team], config: &SquadConfig) -> Result<Vec<TeamWithPlayers>>
+{
+    if players.iter().all(|player| player.club.is_none())
+    {
+        return Err(Error::Validation("no player has a club, crawl the club squads first".to_owned()));
+    }
+
+    let mut teams_with_players: Vec<TeamWithPlayers> = Vec::new();
+    let mut problems: Vec<String> = Vec::new();
+
+    for team in teams
+    {
+        let mut team_players = TeamWithPlayers { team: team.clone(), ..Default::default() };
+
+        for position in POSITIONS.iter()
+        {
+            let mut club_players: Vec<&Player> = players.iter()
+                .filter(|player| player.position == *position && plays_for(player, team))
+                .collect();
+
+            let needed = config.count(position) as usize;
+
+            if club_players.len() < needed
+            {
+                problems.push(format!("{} has {} of {} {:?} players", team.name, club_players.len(), needed, position));
+                continue;
+            }
+
+            //Best players first, ties keep the crawled order.
+            club_players.sort_by_key(|player| std::cmp::Reverse(player.rating()));
+            team_players.players.extend(club_players.into_iter().take(needed).cloned());
+        }
+
+        teams_with_players.push(team_players);
+    }
+
+    if !problems.is_empty()
+    {
+        return Err(Error::Validation(format!("can't build squads from real clubs: {}", problems.join(", "))));
+    }
+
+    return Ok(teams_with_players);
+}
+
+fn plays_for(player: &Player, team: &Team) -> bool
+{
+    return player.club.as_ref().map(|club| club.eq_ignore_ascii_case(&team.name)).unwrap_or(false);
+}
@@ -0,0 +1,81 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::path::Path;
+use rand::Rng;
+
+use crate::crawler::{ self, CrawlConfig };
+use crate::crawler::fetch::HttpFetcher;
+use crate::entities::{ Player, Team, TeamStrength };
+use crate::error::{ Error, Result };
+
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Result<T>
+{
+    let json = std::fs::read_to_string(path)
+        .map_err(|error| Error::Io(path.to_path_buf(), error))?;
+
+    return serde_json::from_str(&json)
+        .map_err(|error| Error::Json(path.to_path_buf(), error));
+}
+
+pub fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()>
+{
+    let json: String = serde_json::to_string_pretty(value)
+        .map_err(|error| Error::Json(path.to_path_buf(), error))?;
+
+    return std::fs::write(path, json)
+        .map_err(|error| Error::Io(path.to_path_buf(), error));
+}
+
+/// Load players from `path`, crawling them as `crawl` says and saving them
+/// there first when the file doesn't exist yet. Fails when two players share an id.
+pub fn load_players(path: &Path, crawl: &CrawlConfig, rng: &mut impl Rng) -> Result<Vec<Player>>
+{
+    if !path.exists()
+    {
+        let players: Vec<Player> = crawler::crawl(&mut HttpFetcher::new(crawl)?, crawl, rng)?.players;
+        save_json(path, &players)?;
+        return Ok(players);
+    }
+
+    let players: Vec<Player> = load_json(path)?;
+    let mut ids: HashSet<u32> = HashSet::new();
+
+    if let Some(duplicate) = players.iter().find(|player| !ids.insert(player.id))
+    {
+        return Err(Error::Validation(format!("{} has more than one player with id {}", path.display(), duplicate.id)));
+    }
+
+    return Ok(players);
+}
+
+pub fn load_teams(path: &Path) -> Result<Vec<Team>>
+{
+    return load_json(path);
+}
+
+/// Load team strengths from `path`, falling back to the legacy standings array
+/// at `legacy_path` where entry `n` belongs to team id `n + 1`. Fails when the
+/// strengths don't cover exactly the given teams.
+pub fn load_strengths(path: &Path, legacy_path: &Path, teams: &[Team]) -> Result<Vec<TeamStrength>>
+{
+    let strengths: Vec<TeamStrength> = if path.exists() || !legacy_path.exists()
+    {
+        load_json(path)?
+    }
+    else
+    {
+        let standings: Vec<u32> = load_json(legacy_path)?;
+
+        standings.iter().enumerate().map(|(index, rating)| TeamStrength {
+            team_id: index as u32 + 1,
+            rating: *rating,
+            form: 1.0,
+            source_date: String::new()
+        }).collect()
+    };
+
+    TeamStrength::validate(&strengths, teams)?;
+
+    return Ok(strengths);
+}
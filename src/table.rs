@@ -0,0 +1,214 @@
+use serde::{ Serialize, Deserialize };
+use std::str::{ FromStr };
+
+use crate::entities::{ Match, Team };
+
+const POINTS_WIN: u32 = 3;
+const POINTS_DRAW: u32 = 1;
+
+/// How teams level on points are separated, applied in the order given.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Tiebreaker
+{
+    GoalDifference,
+    GoalsScored,
+    HeadToHead
+}
+
+impl FromStr for Tiebreaker
+{
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err>
+    {
+        match string.to_lowercase().as_ref() {
+            "gd" | "goal-difference" => { Ok(Tiebreaker::GoalDifference) },
+            "gs" | "goals-scored" => { Ok(Tiebreaker::GoalsScored) },
+            "h2h" | "head-to-head" => { Ok(Tiebreaker::HeadToHead) },
+            _ => { Err(()) }
+        }
+    }
+}
+
+pub const DEFAULT_TIEBREAKERS: [Tiebreaker; 3] = [
+    Tiebreaker::GoalDifference,
+    Tiebreaker::GoalsScored,
+    Tiebreaker::HeadToHead
+];
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct TableRow
+{
+    pub position: u32,
+    pub team_id: u32,
+    pub team_name: String,
+    pub played: u32,
+    pub won: u32,
+    pub drawn: u32,
+    pub lost: u32,
+    pub goals_for: u32,
+    pub goals_against: u32,
+    pub goal_difference: i32,
+    pub points: u32
+}
+
+impl TableRow
+{
+    fn add_result(&mut self, scored: u32, conceded: u32)
+    {
+        self.played += 1;
+        self.goals_for += scored;
+        self.goals_against += conceded;
+        self.goal_difference = self.goals_for as i32 - self.goals_against as i32;
+
+        if scored > conceded
+        {
+            self.won += 1;
+            self.points += POINTS_WIN;
+        }
+        else if scored == conceded
+        {
+            self.drawn += 1;
+            self.points += POINTS_DRAW;
+        }
+        else
+        {
+            self.lost += 1;
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct LeagueTable
+{
+    pub rows: Vec<TableRow>
+}
+
+impl LeagueTable
+{
+    /// Build the standings from played matches, ranking by points and then by
+    /// `tiebreakers` in order. Teams still level after that are ordered by name.
+    pub fn compute(teams: &[Team], matches: &[Match], tiebreakers: &[Tiebreaker]) -> LeagueTable
+    {
+        let mut rows: Vec<TableRow> = teams.iter()
+            .map(|team| TableRow { team_id: team.id, team_name: team.name.clone(), ..Default::default() })
+            .collect();
+
+        for game in matches
+        {
+            let (home_goals, out_goals) = game.score();
+
+            if let Some(row) = rows.iter_mut().find(|row| row.team_id == game.team_home)
+            {
+                row.add_result(home_goals, out_goals);
+            }
+
+            if let Some(row) = rows.iter_mut().find(|row| row.team_id == game.team_out)
+            {
+                row.add_result(out_goals, home_goals);
+            }
+        }
+
+        rows.sort_by(|a, b| b.points.cmp(&a.points).then_with(|| a.team_name.cmp(&b.team_name)));
+
+        let mut ranked: Vec<TableRow> = Vec::new();
+
+        for group in split_equal(rows, |row| row.points as i64)
+        {
+            ranked.extend(break_ties(group, matches, tiebreakers));
+        }
+
+        for (index, row) in ranked.iter_mut().enumerate()
+        {
+            row.position = index as u32 + 1;
+        }
+
+        return LeagueTable { rows: ranked };
+    }
+
+    pub fn print(&self)
+    {
+        println!("{:>3}  {:<24} {:>3} {:>3} {:>3} {:>3} {:>4} {:>4} {:>4} {:>4}",
+            "#", "Team", "P", "W", "D", "L", "GF", "GA", "GD", "Pts");
+
+        for row in &self.rows
+        {
+            println!("{:>3}  {:<24} {:>3} {:>3} {:>3} {:>3} {:>4} {:>4} {:>+4} {:>4}",
+                row.position, row.team_name, row.played, row.won, row.drawn, row.lost,
+                row.goals_for, row.goals_against, row.goal_difference, row.points);
+        }
+    }
+}
+
+/// Order a group of teams level on points by applying the first tiebreaker,
+/// then recursing into whatever is still level with the remaining ones.
+fn break_ties(group: Vec<TableRow>, matches: &[Match], tiebreakers: &[Tiebreaker]) -> Vec<TableRow>
+{
+    if group.len() < 2 || tiebreakers.is_empty()
+    {
+        return group;
+    }
+
+    let mut group = group;
+    let keys: Vec<(u32, i64)> = group.iter()
+        .map(|row| (row.team_id, tiebreak_key(row, &group, matches, tiebreakers[0])))
+        .collect();
+
+    let key_of = |row: &TableRow| keys.iter().find(|(id, _)| *id == row.team_id).unwrap().1;
+    group.sort_by_key(|row| std::cmp::Reverse(key_of(row)));
+
+    let mut ordered: Vec<TableRow> = Vec::new();
+
+    for subgroup in split_equal(group, key_of)
+    {
+        ordered.extend(break_ties(subgroup, matches, &tiebreakers[1..]));
+    }
+
+    return ordered;
+}
+
+fn tiebreak_key(row: &TableRow, group: &[TableRow], matches: &[Match], tiebreaker: Tiebreaker) -> i64
+{
+    match tiebreaker
+    {
+        Tiebreaker::GoalDifference => { row.goal_difference as i64 },
+        Tiebreaker::GoalsScored => { row.goals_for as i64 },
+        Tiebreaker::HeadToHead => {
+            //Points from the matches played among the tied teams only.
+            let in_group = |id: u32| group.iter().any(|other| other.team_id == id);
+            let mut mini_row = TableRow::default();
+
+            for game in matches.iter().filter(|game| in_group(game.team_home) && in_group(game.team_out))
+            {
+                let (home_goals, out_goals) = game.score();
+
+                if game.team_home == row.team_id
+                {
+                    mini_row.add_result(home_goals, out_goals);
+                }
+                else if game.team_out == row.team_id
+                {
+                    mini_row.add_result(out_goals, home_goals);
+                }
+            }
+
+            mini_row.points as i64
+        }
+    }
+}
+
+/// Split already sorted rows into runs that share the same key.
+fn split_equal(rows: Vec<TableRow>, key: impl Fn(&TableRow) -> i64) -> Vec<Vec<TableRow>>
+{
+    let mut groups: Vec<Vec<TableRow>> = Vec::new();
+
+    for row in rows
+    {
+        match groups.last_mut() {
+            Some(group) if key(&group[0]) == key(&row) => { group.push(row); },
+            _ => { groups.push(vec![row]); }
+        }
+    }
+
+    return groups;
+}
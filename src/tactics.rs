@@ -0,0 +1,182 @@
+use serde::{ Serialize, Deserialize };
+use std::str::{ FromStr };
+
+use crate::entities::TeamWithPlayers;
+use crate::error::{ Error, Result };
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Formation
+{
+    #[default]
+    #[serde(rename = "4-3-3")]
+    F433,
+    #[serde(rename = "4-4-2")]
+    F442,
+    #[serde(rename = "4-5-1")]
+    F451,
+    #[serde(rename = "3-5-2")]
+    F352,
+    #[serde(rename = "3-4-3")]
+    F343,
+    #[serde(rename = "5-3-2")]
+    F532,
+    #[serde(rename = "5-4-1")]
+    F541
+}
+
+impl FromStr for Formation
+{
+    type Err = ();
+
+    fn from_str(string: &str) -> std::result::Result<Self, Self::Err>
+    {
+        match string {
+            "4-3-3" => { Ok(Formation::F433) },
+            "4-4-2" => { Ok(Formation::F442) },
+            "4-5-1" => { Ok(Formation::F451) },
+            "3-5-2" => { Ok(Formation::F352) },
+            "3-4-3" => { Ok(Formation::F343) },
+            "5-3-2" => { Ok(Formation::F532) },
+            "5-4-1" => { Ok(Formation::F541) },
+            _ => { Err(()) }
+        }
+    }
+}
+
+impl Formation
+{
+    /// Outfield players per line as (defenders, midfielders, attackers).
+    pub fn lines(&self) -> (u32, u32, u32)
+    {
+        match self
+        {
+            Formation::F433 => (4, 3, 3),
+            Formation::F442 => (4, 4, 2),
+            Formation::F451 => (4, 5, 1),
+            Formation::F352 => (3, 5, 2),
+            Formation::F343 => (3, 4, 3),
+            Formation::F532 => (5, 3, 2),
+            Formation::F541 => (5, 4, 1)
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Tactics
+{
+    Attacking,
+    #[default]
+    Balanced,
+    Defensive,
+    Counter,
+    HighPress
+}
+
+impl FromStr for Tactics
+{
+    type Err = ();
+
+    fn from_str(string: &str) -> std::result::Result<Self, Self::Err>
+    {
+        match string.to_lowercase().as_ref() {
+            "attacking" => { Ok(Tactics::Attacking) },
+            "balanced" => { Ok(Tactics::Balanced) },
+            "defensive" => { Ok(Tactics::Defensive) },
+            "counter" => { Ok(Tactics::Counter) },
+            "high-press" | "highpress" => { Ok(Tactics::HighPress) },
+            _ => { Err(()) }
+        }
+    }
+}
+
+/// The situations in which a team moves the ball up the pitch.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Phase
+{
+    /// Getting past the opponent's defence.
+    Attack,
+    /// Winning the ball battle in midfield.
+    Midfield,
+    /// Winning the ball back in its own half.
+    Repel
+}
+
+impl Tactics
+{
+    /// Multipliers on (attack, midfield, defence) strength.
+    pub fn line_factors(&self) -> (f32, f32, f32)
+    {
+        match self
+        {
+            Tactics::Attacking => (1.1, 1.0, 0.9),
+            Tactics::Balanced => (1.0, 1.0, 1.0),
+            Tactics::Defensive => (0.9, 1.0, 1.1),
+            Tactics::Counter => (1.0, 0.95, 1.05),
+            Tactics::HighPress => (1.0, 1.1, 0.95)
+        }
+    }
+
+    /// Multiplier on how far the ball moves when the team wins `phase`.
+    pub fn advance_factor(&self, phase: Phase) -> f32
+    {
+        match (self, phase)
+        {
+            (Tactics::Attacking, Phase::Attack) => 1.15,
+            (Tactics::Attacking, Phase::Midfield) => 1.05,
+            (Tactics::Attacking, Phase::Repel) => 0.9,
+            (Tactics::Defensive, Phase::Attack) => 0.9,
+            (Tactics::Defensive, Phase::Midfield) => 0.95,
+            (Tactics::Defensive, Phase::Repel) => 1.05,
+            (Tactics::Counter, Phase::Attack) => 0.95,
+            (Tactics::Counter, Phase::Midfield) => 0.9,
+            (Tactics::Counter, Phase::Repel) => 1.35,
+            (Tactics::HighPress, Phase::Midfield) => 1.15,
+            (Tactics::HighPress, Phase::Repel) => 0.95,
+            _ => 1.0
+        }
+    }
+}
+
+/// How a team lines up and plays.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct TeamSetup
+{
+    #[serde(default)]
+    pub formation: Formation,
+    #[serde(default)]
+    pub tactics: Tactics
+}
+
+impl TeamSetup
+{
+    /// Tactics multipliers on (attack, midfield, defence). The formation
+    /// already shows in how many players the lineup puts in every line.
+    pub fn line_factors(&self) -> (f32, f32, f32)
+    {
+        return self.tactics.line_factors();
+    }
+}
+
+/// A setup override for one team, as read from `tactics.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TeamTactics
+{
+    pub team_id: u32,
+    #[serde(flatten)]
+    pub setup: TeamSetup
+}
+
+/// Apply per-team setup overrides. Fails on overrides for teams that aren't playing.
+pub fn apply_tactics(teams: &mut [TeamWithPlayers], overrides: &[TeamTactics]) -> Result<()>
+{
+    for team_tactics in overrides
+    {
+        match teams.iter_mut().find(|team| team.team.id == team_tactics.team_id)
+        {
+            Some(team) => { team.setup = team_tactics.setup; },
+            None => { return Err(Error::Validation(format!("tactics for unknown team {}", team_tactics.team_id))); }
+        }
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,77 @@
+#![allow(clippy::needless_return)]
+
+use soccer_sim::absence::{ AbsenceReason, Absences };
+use soccer_sim::config::EngineConfig;
+use soccer_sim::entities::{ Card, CardKind, Match };
+
+const PLAYER: u32 = 10;
+const TEAM: u32 = 1;
+
+/// A match in which `PLAYER` got a card of `kind`, or nothing when `None`.
+fn booked(matchday: u32, kind: Option<CardKind>) -> Match
+{
+    let cards: Vec<Card> = kind.into_iter()
+        .map(|kind| Card { time: 30, team_id: TEAM, player_id: PLAYER, kind, added_time: 0 })
+        .collect();
+
+    return Match { matchday, team_home: TEAM, team_out: 2, cards, ..Default::default() };
+}
+
+fn config() -> EngineConfig
+{
+    return EngineConfig { yellow_cards_for_ban: 3, red_card_ban: 2, ..Default::default() };
+}
+
+/// Whether `PLAYER` can't be picked on each matchday, playing `cards` in turn.
+fn banned_on(cards: &[Option<CardKind>]) -> Vec<bool>
+{
+    let mut absences: Absences = Absences::default();
+    let mut banned: Vec<bool> = Vec::new();
+
+    for (index, kind) in cards.iter().enumerate()
+    {
+        let matchday = index as u32 + 1;
+        banned.push(absences.miss_match(TEAM, matchday * 7).contains(&PLAYER));
+        absences.record(&booked(matchday, *kind), matchday * 7, &config());
+    }
+
+    return banned;
+}
+
+#[test]
+fn yellow_cards_add_up_to_a_one_match_ban()
+{
+    let yellow = Some(CardKind::Yellow);
+    let banned: Vec<bool> = banned_on(&[yellow, yellow, yellow, None, yellow, yellow, yellow, None, None]);
+
+    //The count starts over after a ban, so the sixth yellow earns the next one.
+    assert_eq!(banned, vec![false, false, false, true, false, false, false, true, false]);
+}
+
+#[test]
+fn yellow_cards_never_ban_when_accumulation_is_off()
+{
+    let mut absences: Absences = Absences::default();
+    let config = EngineConfig { yellow_cards_for_ban: 0, ..config() };
+
+    for matchday in 1..=10
+    {
+        absences.record(&booked(matchday, Some(CardKind::Yellow)), matchday * 7, &config);
+    }
+
+    assert!(absences.into_report().is_empty());
+}
+
+#[test]
+fn sending_offs_ban_for_their_own_length()
+{
+    assert_eq!(banned_on(&[Some(CardKind::SecondYellow), None, None]), vec![false, true, false]);
+    assert_eq!(banned_on(&[Some(CardKind::Red), None, None, None]), vec![false, true, true, false]);
+
+    let mut absences: Absences = Absences::default();
+    absences.record(&booked(1, Some(CardKind::Red)), 7, &config());
+
+    let report = absences.into_report();
+    assert_eq!(report.len(), 1);
+    assert_eq!((report[0].reason, report[0].length, report[0].matchday), (AbsenceReason::Suspension, 2, 1));
+}
@@ -0,0 +1,115 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use chrono::NaiveDate;
+
+use soccer_sim::career;
+use soccer_sim::entities::{ Attributes, Player, Position, TeamWithPlayers };
+use soccer_sim::rng;
+use soccer_sim::squad::SquadConfig;
+
+fn season_start() -> NaiveDate
+{
+    return NaiveDate::from_ymd_opt(2022, 8, 1).unwrap();
+}
+
+/// A player who is `age` on the season start.
+fn player_aged(age: i32, position: Position, quality: u8) -> Player
+{
+    let dob: NaiveDate = NaiveDate::from_ymd_opt(2022 - age, 3, 1).unwrap();
+
+    return Player { id: 1, dob: Some(dob), position: position.clone(), attributes: Attributes::from_quality(quality, &position, 70), ..Default::default() };
+}
+
+/// Average change of the attributes over a season, for many players of `age`.
+fn average_development(age: i32, position: Position) -> f32
+{
+    let mut rng = rng::from_seed(age as u64);
+    let mut total: i32 = 0;
+    let players: i32 = 200;
+
+    for _ in 0..players
+    {
+        let mut player: Player = player_aged(age, position.clone(), 60);
+        career::develop(&mut player, season_start(), &mut rng);
+
+        let attributes: Attributes = player.attributes;
+        total += [attributes.finishing, attributes.passing, attributes.tackling, attributes.pace].iter().map(|value| *value as i32 - 60).sum::<i32>();
+    }
+
+    return total as f32 / (players * 4) as f32;
+}
+
+#[test]
+fn young_players_improve_and_old_players_decline()
+{
+    let curve: Vec<f32> = [18, 22, 26, 29, 32, 35].iter().map(|age| average_development(*age, Position::Midfield)).collect();
+
+    assert!(curve[0] > 3.0, "{:?}", curve);
+    assert!(curve[1] > 1.0, "{:?}", curve);
+    assert!(curve[2].abs() < 0.5, "{:?}", curve);
+    assert!(curve[3] < 0.0, "{:?}", curve);
+    assert!(curve[5] < -4.0, "{:?}", curve);
+
+    //Every year past the peak costs more than the last.
+    assert!(curve.windows(2).skip(2).all(|pair| pair[1] < pair[0]), "{:?}", curve);
+}
+
+#[test]
+fn keepers_decline_later_than_outfield_players()
+{
+    assert!(average_development(29, Position::Goalkeeper) > average_development(29, Position::Defender));
+}
+
+#[test]
+fn retirement_follows_age_and_rating()
+{
+    let mut rng = rng::from_seed(3);
+    let date: NaiveDate = season_start();
+
+    assert!(career::retires(&player_aged(35, Position::Defender, 90), date, &mut rng));
+    assert!(career::retires(&player_aged(30, Position::Defender, 44), date, &mut rng));
+    assert!(!career::retires(&player_aged(29, Position::Defender, 20), date, &mut rng));
+    assert!(!career::retires(&Player { dob: None, ..player_aged(40, Position::Defender, 20) }, date, &mut rng));
+
+    //From 32 good players may retire, more likely every year: 25%, 50%, 75%.
+    for (age, chance) in [(31, 0.0), (32, 0.25), (33, 0.5), (34, 0.75)]
+    {
+        let player: Player = player_aged(age, Position::Defender, 70);
+        let retired = (0..400).filter(|_| career::retires(&player, date, &mut rng)).count() as f32 / 400.0;
+
+        assert!((retired - chance).abs() < 0.08, "age {}: {}", age, retired);
+    }
+}
+
+#[test]
+fn youth_intake_fills_the_squad_back_up()
+{
+    let mut team: TeamWithPlayers = common::teams(1).remove(0);
+    let config: SquadConfig = SquadConfig::default();
+
+    //Both keepers and three attackers retired.
+    team.players.retain(|player| player.position != Position::Goalkeeper);
+    let attackers: Vec<u32> = team.players.iter().filter(|player| player.position == Position::Attacker).map(|player| player.id).take(3).collect();
+    team.players.retain(|player| !attackers.contains(&player.id));
+
+    let mut next_id: u32 = 100;
+    let names: Vec<String> = vec!["Arjen Robben".to_owned(), "Wesley Sneijder".to_owned()];
+    let intake: Vec<Player> = career::youth_intake(&team, &config, season_start(), &names, &mut next_id, &mut rng::from_seed(5));
+
+    assert_eq!(intake.len(), 5);
+    assert_eq!(intake.iter().map(|player| player.id).collect::<Vec<u32>>(), vec![100, 101, 102, 103, 104]);
+    assert_eq!(next_id, 105);
+    assert!(intake.iter().all(|player| (16..=18).contains(&player.age_on(season_start()).unwrap())));
+
+    team.players.extend(intake);
+    let count = |position: Position| team.players.iter().filter(|player| player.position == position).count() as u32;
+    let squad = SquadConfig { goalkeepers: count(Position::Goalkeeper), defenders: count(Position::Defender), midfielders: count(Position::Midfield), attackers: count(Position::Attacker) };
+
+    assert_eq!((squad.goalkeepers, squad.attackers), (config.goalkeepers, config.attackers));
+    assert!(squad.validate().is_ok());
+
+    //A full squad takes nobody on.
+    assert!(career::youth_intake(&team, &config, season_start(), &names, &mut next_id, &mut rng::from_seed(5)).is_empty());
+}
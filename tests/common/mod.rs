@@ -0,0 +1,37 @@
+#![allow(dead_code)]
+
+use soccer_sim::entities::{ Attributes, Player, Position, Team, TeamStrength, TeamWithPlayers };
+use soccer_sim::rng;
+use soccer_sim::squad::SquadConfig;
+
+/// Teams 1..=count, each with a full default squad of rolled players.
+pub fn teams(count: u32) -> Vec<TeamWithPlayers>
+{
+    let mut rng = rng::from_seed(count as u64);
+    let config: SquadConfig = SquadConfig::default();
+    let mut next_id: u32 = 1;
+
+    return (1..=count).map(|id| {
+        let mut players: Vec<Player> = Vec::new();
+
+        for position in [Position::Goalkeeper, Position::Defender, Position::Midfield, Position::Attacker]
+        {
+            for _ in 0..config.count(&position)
+            {
+                let attributes: Attributes = Attributes::roll(60, &position, &mut rng);
+                players.push(Player { id: next_id, name: format!("Player {}", next_id), position: position.clone(), attributes, ..Default::default() });
+                next_id += 1;
+            }
+        }
+
+        return TeamWithPlayers { team: Team { id, name: format!("Team {}", id), ..Default::default() }, players, ..Default::default() };
+    }).collect();
+}
+
+/// An equal strength for every team.
+pub fn strengths(teams: &[TeamWithPlayers]) -> Vec<TeamStrength>
+{
+    return teams.iter()
+        .map(|team| TeamStrength { team_id: team.team.id, rating: 70, form: 1.0, source_date: String::new() })
+        .collect();
+}
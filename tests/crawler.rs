@@ -0,0 +1,383 @@
+#![allow(clippy::needless_return)]
+
+use chrono::NaiveDate;
+use std::path::{ Path, PathBuf };
+
+use soccer_sim::crawler::{ self, parse, CrawlConfig, CrawledClubs, CrawledPlayers, Pagination };
+use soccer_sim::crawler::parse::{ ClubLink, ClubPage, Column, ParsedClubs, ParsedPage, SkipReason };
+use soccer_sim::crawler::fetch::{ DirFetcher, Fetcher, HttpFetcher };
+use soccer_sim::crawler::stub::StubServer;
+use soccer_sim::entities::{ Player, Position, Surface, Team, TeamWithPlayers };
+use soccer_sim::error::{ Error, Result };
+use soccer_sim::rng;
+use soccer_sim::squad::{ self, SquadConfig, SquadSource };
+
+fn fixtures() -> PathBuf
+{
+    return Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pages");
+}
+
+/// A league table linking to two club pages, one with the squad on a page of its own.
+fn club_fixtures() -> PathBuf
+{
+    return Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/clubs");
+}
+
+fn clubs_config() -> CrawlConfig
+{
+    return CrawlConfig { clubs_url: Some("/eredivisie/".to_owned()), delay_ms: 0, ..Default::default() };
+}
+
+fn fixture(page: u32) -> String
+{
+    return std::fs::read_to_string(fixtures().join(format!("{}.html", page))).unwrap();
+}
+
+/// A German page with its columns in another order and rows that give no player.
+fn messy_page() -> String
+{
+    return std::fs::read_to_string(fixtures().join("../messy_page.html")).unwrap();
+}
+
+fn summary(players: &[Player]) -> Vec<(u32, String, Option<NaiveDate>, String, Position)>
+{
+    return players.iter()
+        .map(|player| (player.id, player.name.clone(), player.dob, player.height.clone(), player.position.clone()))
+        .collect();
+}
+
+/// Serves the fixture pages with an empty page in between.
+struct GapFetcher;
+
+impl Fetcher for GapFetcher
+{
+    fn fetch_page(&mut self, page: u32) -> Result<Option<String>>
+    {
+        match page
+        {
+            1 | 3 => Ok(Some(fixture(1))),
+            2 => Ok(Some("<html><body><table class=\"standard_tabelle\"></table></body></html>".to_owned())),
+            _ => Ok(None)
+        }
+    }
+}
+
+/// Serves the first fixture page and then the messy one.
+struct MessyFetcher;
+
+impl Fetcher for MessyFetcher
+{
+    fn fetch_page(&mut self, page: u32) -> Result<Option<String>>
+    {
+        match page
+        {
+            1 => Ok(Some(fixture(1))),
+            2 => Ok(Some(messy_page())),
+            _ => Ok(None)
+        }
+    }
+}
+
+fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate>
+{
+    return NaiveDate::from_ymd_opt(year, month, day);
+}
+
+#[test]
+fn parses_players_from_a_page()
+{
+    let players: Vec<Player> = parse::parse_players(&fixture(1), 0).unwrap().players;
+
+    assert_eq!(summary(&players), vec![
+        (0, "Remko Pasveer".to_owned(), date(1983, 11, 8), "187 cm".to_owned(), Position::Goalkeeper),
+        (1, "Jurriën Timber".to_owned(), date(2001, 6, 17), "179 cm".to_owned(), Position::Defender),
+        (2, "Davy Klaassen".to_owned(), date(1993, 2, 21), "179 cm".to_owned(), Position::Midfield),
+        (3, "Sébastien Haller".to_owned(), date(1994, 6, 22), "190 cm".to_owned(), Position::Attacker)
+    ]);
+    assert!(players.iter().all(|player| player.club.is_none()));
+}
+
+#[test]
+fn numbers_players_from_the_first_id()
+{
+    let players: Vec<Player> = parse::parse_players(&fixture(2), 50).unwrap().players;
+    let ids: Vec<u32> = players.iter().map(|player| player.id).collect();
+
+    assert_eq!(ids, vec![50, 51]);
+    assert_eq!(players[1].name, "Orkun Kökçü");
+}
+
+#[test]
+fn page_without_player_table_has_no_players()
+{
+    let players: Vec<Player> = parse::parse_players("<html><body><p>Geen spelers</p></body></html>", 0).unwrap().players;
+
+    assert!(players.is_empty());
+}
+
+#[test]
+fn maps_columns_by_their_header()
+{
+    let page: ParsedPage = parse::parse_players(&messy_page(), 10).unwrap();
+
+    assert_eq!(summary(&page.players), vec![
+        (10, "Thomas Müller".to_owned(), date(1989, 9, 13), "185 cm".to_owned(), Position::Goalkeeper),
+        (11, "Niklas Süle".to_owned(), None, "195 cm".to_owned(), Position::Defender)
+    ]);
+}
+
+#[test]
+fn reports_the_rows_it_skips()
+{
+    let skipped: Vec<(usize, usize, SkipReason)> = parse::parse_players(&messy_page(), 0).unwrap().skipped
+        .into_iter()
+        .map(|row| (row.table, row.row, row.reason))
+        .collect();
+
+    assert_eq!(skipped, vec![
+        (1, 1, SkipReason::NoHeader),
+        (2, 4, SkipReason::MergedCells(Column::Position, Column::Name)),
+        (2, 5, SkipReason::EmptyName),
+        (2, 6, SkipReason::MissingCell(Column::Height)),
+        (2, 8, SkipReason::UnknownPosition("TR".to_owned())),
+        (2, 9, SkipReason::InvalidDate("unbekannt".to_owned()))
+    ]);
+}
+
+#[test]
+fn skipped_rows_keep_their_text()
+{
+    let html = "<table class=\"standard_tabelle\"><tr><th>Speler</th><th>Positie</th></tr><tr><td colspan=\"2\">Bron: KNVB &amp; Eredivisie</td></tr></table>";
+    let page: ParsedPage = parse::parse_players(html, 0).unwrap();
+
+    assert!(page.players.is_empty());
+    assert_eq!(page.skipped[0].text, "Bron: KNVB & Eredivisie");
+    assert_eq!(page.skipped[0].to_string(), "table 1, row 2: one cell spans Name and Position (Bron: KNVB & Eredivisie)");
+}
+
+#[test]
+fn crawl_reports_skipped_rows_with_their_page()
+{
+    let crawled: CrawledPlayers = crawler::crawl(&mut MessyFetcher, &CrawlConfig::default(), &mut rng::from_seed(1)).unwrap();
+
+    assert_eq!(crawled.players.len(), 6);
+    assert_eq!(crawled.skipped.iter().map(|(page, _)| *page).collect::<Vec<u32>>(), vec![2, 2, 2, 2, 2, 2]);
+}
+
+#[test]
+fn dir_fetcher_reads_saved_pages()
+{
+    let mut fetcher = DirFetcher::new(&fixtures());
+
+    assert_eq!(fetcher.fetch_page(1).unwrap(), Some(fixture(1)));
+    assert_eq!(fetcher.fetch_page(3).unwrap(), None);
+}
+
+#[test]
+fn crawls_saved_pages_until_one_is_missing()
+{
+    let players: Vec<Player> = crawler::crawl(&mut DirFetcher::new(&fixtures()), &CrawlConfig::default(), &mut rng::from_seed(1)).unwrap().players;
+    let ids: Vec<u32> = players.iter().map(|player| player.id).collect();
+
+    assert_eq!(ids, vec![0, 1, 2, 3, 4, 5]);
+    assert!(players.iter().all(|player| player.attributes.stamina >= 50));
+}
+
+#[test]
+fn player_ids_stay_unique_when_a_page_has_more_rows_than_per_page()
+{
+    let config = CrawlConfig { per_page: 2, ..Default::default() };
+    let players: Vec<Player> = crawler::crawl(&mut MessyFetcher, &config, &mut rng::from_seed(1)).unwrap().players;
+
+    //Four players on the first page, two on the second.
+    assert_eq!(players.iter().map(|player| player.id).collect::<Vec<u32>>(), vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn crawl_is_deterministic_for_a_seed()
+{
+    let first: Vec<Player> = crawler::crawl(&mut DirFetcher::new(&fixtures()), &CrawlConfig::default(), &mut rng::from_seed(7)).unwrap().players;
+    let second: Vec<Player> = crawler::crawl(&mut DirFetcher::new(&fixtures()), &CrawlConfig::default(), &mut rng::from_seed(7)).unwrap().players;
+
+    let ratings = |players: &[Player]| -> Vec<u8> { players.iter().map(|player| player.rating()).collect() };
+
+    assert_eq!(ratings(&first), ratings(&second));
+}
+
+#[test]
+fn crawls_the_same_players_over_the_stub_server()
+{
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    let config = CrawlConfig { base_url: server.url(), delay_ms: 0, ..Default::default() };
+    let mut http = HttpFetcher::new(&config).unwrap();
+
+    assert_eq!(http.fetch_page(2).unwrap(), Some(fixture(2)));
+    assert_eq!(http.fetch_page(3).unwrap(), None);
+
+    let over_http: Vec<Player> = crawler::crawl(&mut http, &config, &mut rng::from_seed(3)).unwrap().players;
+    let from_dir: Vec<Player> = crawler::crawl(&mut DirFetcher::new(&fixtures()), &CrawlConfig::default(), &mut rng::from_seed(3)).unwrap().players;
+
+    assert_eq!(summary(&over_http), summary(&from_dir));
+}
+
+#[test]
+fn stops_at_the_first_page_without_players()
+{
+    let players: Vec<Player> = crawler::crawl(&mut GapFetcher, &CrawlConfig::default(), &mut rng::from_seed(1)).unwrap().players;
+
+    assert_eq!(players.len(), 4);
+}
+
+#[test]
+fn stops_after_max_pages()
+{
+    let config = CrawlConfig { max_pages: Some(1), ..Default::default() };
+    let players: Vec<Player> = crawler::crawl(&mut DirFetcher::new(&fixtures()), &config, &mut rng::from_seed(1)).unwrap().players;
+
+    assert_eq!(players.len(), 4);
+}
+
+#[test]
+fn builds_page_urls_for_every_pagination()
+{
+    let mut config = CrawlConfig { base_url: "https://example.com/players/".to_owned(), per_page: 50, ..Default::default() };
+    assert_eq!(config.page_url(3), "https://example.com/players/3");
+
+    config.pagination = "query:page".parse::<Pagination>().unwrap();
+    assert_eq!(config.page_url(3), "https://example.com/players?page=3");
+
+    config.base_url = "https://example.com/players?season=2021".to_owned();
+    config.pagination = "offset:start".parse::<Pagination>().unwrap();
+    assert_eq!(config.page_url(3), "https://example.com/players?season=2021&start=100");
+
+    assert!("query".parse::<Pagination>().is_err());
+}
+
+#[test]
+fn http_crawl_needs_a_base_url()
+{
+    assert!(HttpFetcher::new(&CrawlConfig::default()).is_err());
+}
+
+#[test]
+fn parses_club_links_from_a_league_table()
+{
+    let html: String = std::fs::read_to_string(club_fixtures().join("eredivisie.html")).unwrap();
+    let parsed: ParsedClubs = parse::parse_club_links(&html).unwrap();
+
+    assert_eq!(parsed.clubs, vec![
+        ClubLink { name: "Ajax".to_owned(), link: "/teams/ajax/".to_owned() },
+        ClubLink { name: "Feyenoord".to_owned(), link: "/teams/feyenoord/".to_owned() }
+    ]);
+    assert_eq!(parsed.skipped.iter().map(|row| (row.row, row.reason.clone())).collect::<Vec<_>>(), vec![(3, SkipReason::NoLink)]);
+}
+
+#[test]
+fn parses_club_details()
+{
+    let html: String = std::fs::read_to_string(club_fixtures().join("teams/ajax.html")).unwrap();
+
+    assert_eq!(parse::parse_club(&html).unwrap(), ClubPage {
+        city: Some("Amsterdam".to_owned()),
+        stadium: Some("Johan Cruijff ArenA".to_owned()),
+        surface: Some("Hybride".to_owned()),
+        founded: Some(1900),
+        squad_link: Some("/teams/ajax/selectie/".to_owned())
+    });
+}
+
+#[test]
+fn dir_fetcher_reads_linked_pages_by_path()
+{
+    let mut fetcher = DirFetcher::new(&club_fixtures());
+
+    assert!(fetcher.fetch_link("/teams/ajax/").unwrap().is_some());
+    assert!(fetcher.fetch_link("https://example.com/teams/ajax/selectie/?season=2021").unwrap().is_some());
+    assert_eq!(fetcher.fetch_link("/teams/psv/").unwrap(), None);
+    assert_eq!(fetcher.fetch_link("/../pages/1").unwrap(), None);
+}
+
+#[test]
+fn crawls_clubs_with_their_squads()
+{
+    let crawled: CrawledClubs = crawler::crawl_clubs(&mut DirFetcher::new(&club_fixtures()), &clubs_config(), &mut rng::from_seed(1)).unwrap();
+
+    let teams: Vec<(u32, &str, &str, &str, Option<u32>)> = crawled.teams.iter()
+        .map(|team| (team.id, team.name.as_str(), team.city.as_str(), team.stadium.as_str(), team.founded))
+        .collect();
+
+    assert_eq!(teams, vec![
+        (1, "Ajax", "Amsterdam", "Johan Cruijff ArenA", Some(1900)),
+        (2, "Feyenoord", "Rotterdam", "De Kuip", Some(1908))
+    ]);
+    assert!(matches!(crawled.teams[1].surface, Surface::Grass));
+
+    let ids: Vec<u32> = crawled.players.iter().map(|player| player.id).collect();
+    assert_eq!(ids, (0..36).collect::<Vec<u32>>());
+    assert_eq!(crawled.players[1].name, "André Onana");
+    assert_eq!(crawled.players[1].club.as_deref(), Some("Ajax"));
+    assert_eq!(crawled.players[20].name, "Marcos Senesi");
+    assert_eq!(crawled.players[20].club.as_deref(), Some("Feyenoord"));
+
+    //PSV has no page to crawl and Feyenoord lists a loan heading in its squad.
+    assert_eq!(crawled.skipped.iter().map(|(link, row)| (link.as_str(), row.row)).collect::<Vec<_>>(), vec![("/eredivisie/", 3), ("/teams/feyenoord/", 11)]);
+}
+
+#[test]
+fn crawled_clubs_keep_the_ids_they_already_have()
+{
+    let mut crawled: CrawledClubs = crawler::crawl_clubs(&mut DirFetcher::new(&club_fixtures()), &clubs_config(), &mut rng::from_seed(1)).unwrap();
+    let existing: Vec<Team> = vec![
+        Team { id: 4, name: "PSV".to_owned(), ..Default::default() },
+        Team { id: 7, name: "FEYENOORD".to_owned(), since: 1956, ..Default::default() }
+    ];
+
+    crawled.keep_team_ids(&existing);
+
+    let teams: Vec<(u32, &str, u32)> = crawled.teams.iter().map(|team| (team.id, team.name.as_str(), team.since)).collect();
+    assert_eq!(teams, vec![(8, "Ajax", 0), (7, "Feyenoord", 1956)]);
+}
+
+#[test]
+fn builds_squads_from_crawled_rosters()
+{
+    let crawled: CrawledClubs = crawler::crawl_clubs(&mut DirFetcher::new(&club_fixtures()), &clubs_config(), &mut rng::from_seed(1)).unwrap();
+    let config = SquadConfig { attackers: 4, midfielders: 6, defenders: 6, goalkeepers: 2 };
+
+    let squads: Vec<TeamWithPlayers> = squad::build_squads(&crawled.players, &crawled.teams, &config, SquadSource::RealClubs, &mut rng::from_seed(1)).unwrap();
+
+    for team in &squads
+    {
+        assert_eq!(team.players.len(), 18);
+        assert!(team.players.iter().all(|player| player.club.as_deref() == Some(team.team.name.as_str())));
+    }
+}
+
+#[test]
+fn crawls_the_same_clubs_over_the_stub_server()
+{
+    let server: StubServer = StubServer::start(&club_fixtures()).unwrap();
+    let config = CrawlConfig { base_url: server.url(), ..clubs_config() };
+
+    let over_http: CrawledClubs = crawler::crawl_clubs(&mut HttpFetcher::new(&config).unwrap(), &config, &mut rng::from_seed(3)).unwrap();
+    let from_dir: CrawledClubs = crawler::crawl_clubs(&mut DirFetcher::new(&club_fixtures()), &clubs_config(), &mut rng::from_seed(3)).unwrap();
+
+    assert_eq!(summary(&over_http.players), summary(&from_dir.players));
+    assert_eq!(over_http.teams.len(), 2);
+}
+
+#[test]
+fn crawling_clubs_needs_a_fetcher_that_follows_links()
+{
+    let result = crawler::crawl_clubs(&mut GapFetcher, &clubs_config(), &mut rng::from_seed(1));
+
+    assert!(matches!(result, Err(Error::Crawl(message)) if message == "this fetcher can't follow links"));
+}
+
+#[test]
+fn crawling_clubs_needs_a_clubs_url()
+{
+    let config = CrawlConfig { clubs_url: None, ..clubs_config() };
+
+    assert!(crawler::crawl_clubs(&mut DirFetcher::new(&club_fixtures()), &config, &mut rng::from_seed(1)).is_err());
+}
@@ -0,0 +1,105 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::collections::HashSet;
+
+use soccer_sim::config::EngineConfig;
+use soccer_sim::cup::{ self, Cup, CupConfig, CupTie, Decision, Draw };
+use soccer_sim::entities::{ TeamStrength, TeamWithPlayers };
+
+/// A cup of `count` teams where a lower id is a stronger team.
+fn play(count: u32, draw: Draw, seed: u64) -> Cup
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(count);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams).into_iter()
+        .map(|strength| TeamStrength { rating: 90 - strength.team_id, ..strength })
+        .collect();
+
+    return cup::play_cup(&teams, &strengths, &CupConfig { draw, ..Default::default() }, seed).unwrap();
+}
+
+#[test]
+fn byes_leave_a_power_of_two_for_the_second_round()
+{
+    for (count, byes) in [(5, vec![1, 2, 3]), (6, vec![1, 2]), (7, vec![1])]
+    {
+        let cup: Cup = play(count, Draw::Seeded, 11);
+        let first = &cup.rounds[0];
+
+        //The top seeds sit the first round out.
+        assert_eq!(first.byes, byes, "{} teams", count);
+        assert_eq!(first.ties.len() as u32, (count - byes.len() as u32) / 2, "{} teams", count);
+
+        let second = &cup.rounds[1];
+        assert_eq!(second.ties.len(), 2, "{} teams", count);
+        assert!(second.byes.is_empty());
+        assert_eq!(cup.rounds.len(), 3);
+        assert_eq!(cup.rounds[2].name, "Final");
+    }
+}
+
+#[test]
+fn every_team_is_in_the_first_round_once()
+{
+    for count in 5..=7
+    {
+        let cup: Cup = play(count, Draw::Random, 3);
+        let first = &cup.rounds[0];
+
+        let mut teams: Vec<u32> = first.byes.clone();
+        teams.extend(first.ties.iter().flat_map(|tie| [tie.home, tie.away]));
+
+        assert_eq!(teams.len() as u32, count);
+        assert_eq!(teams.iter().copied().collect::<HashSet<u32>>(), (1..=count).collect::<HashSet<u32>>());
+    }
+}
+
+/// Goals `team_id` scored over the legs of a tie, awarded goals included.
+fn aggregate(tie: &CupTie, team_id: u32) -> u32
+{
+    return tie.legs.iter().map(|leg| {
+        let (home_goals, out_goals) = leg.score();
+        if leg.team_home == team_id { home_goals } else { out_goals }
+    }).sum();
+}
+
+#[test]
+fn a_forfeited_last_leg_counts_towards_the_aggregate()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(4);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams).into_iter()
+        .map(|strength| TeamStrength { rating: 100 - 20 * strength.team_id, ..strength })
+        .collect();
+    let engine = EngineConfig { red_card_chance: 0.02, ..Default::default() };
+    let config = CupConfig { two_legs: true, engine, ..Default::default() };
+    let (mut knocked_out, mut went_through) = (0, 0);
+
+    for seed in 700..760
+    {
+        let cup: Cup = cup::play_cup(&teams, &strengths, &config, seed).unwrap();
+
+        for tie in cup.rounds[0].ties.iter().filter(|tie| tie.legs[1].forfeited_by.is_some())
+        {
+            let forfeited_by: u32 = tie.legs[1].forfeited_by.unwrap();
+            let other: u32 = if forfeited_by == tie.home { tie.away } else { tie.home };
+            let (forfeiter_goals, other_goals) = (aggregate(tie, forfeited_by), aggregate(tie, other));
+
+            assert_eq!(tie.decided_by, Decision::Forfeit);
+
+            //The team that forfeited only goes through when still ahead on aggregate.
+            if forfeiter_goals > other_goals
+            {
+                assert_eq!(tie.winner, forfeited_by, "seed {}", seed);
+                went_through += 1;
+            }
+            else
+            {
+                assert_eq!(tie.winner, other, "seed {}", seed);
+                knocked_out += 1;
+            }
+        }
+    }
+
+    assert!(knocked_out > 0 && went_through > 0, "{} knocked out, {} went through", knocked_out, went_through);
+}
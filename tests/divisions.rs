@@ -0,0 +1,108 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::collections::HashSet;
+
+use soccer_sim::config::EngineConfig;
+use soccer_sim::divisions::{ self, Division, Movement, Playoff, Pyramid, PyramidSeason };
+use soccer_sim::entities::{ TeamStrength, TeamWithPlayers };
+use soccer_sim::error::Error;
+use soccer_sim::table::DEFAULT_TIEBREAKERS;
+
+/// Teams 1 to 5 on top, 6 to 10 below, one straight swap and a playoff for another.
+fn pyramid() -> Pyramid
+{
+    return Pyramid {
+        divisions: vec![
+            Division { name: "Top".to_owned(), team_ids: vec![1, 2, 3, 4, 5] },
+            Division { name: "Second".to_owned(), team_ids: vec![6, 7, 8, 9, 10] }
+        ],
+        movements: vec![Movement {
+            relegated: 1,
+            promoted: 1,
+            playoff: Some(Playoff { upper_places: vec![4], lower_places: vec![2], spots: 1 })
+        }]
+    };
+}
+
+fn validation_error(pyramid: &Pyramid, teams: &[TeamWithPlayers]) -> String
+{
+    match pyramid.validate(teams)
+    {
+        Err(Error::Validation(message)) => message,
+        other => panic!("expected a validation error, got {:?}", other)
+    }
+}
+
+#[test]
+fn a_valid_pyramid_passes()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(10);
+
+    assert!(pyramid().validate(&teams).is_ok());
+    assert!(Pyramid::single(&teams).validate(&teams).is_ok());
+}
+
+#[test]
+fn validate_reports_misplaced_teams()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(10);
+    let mut pyramid: Pyramid = pyramid();
+    pyramid.divisions[1].team_ids = vec![5, 7, 8, 9, 11];
+
+    let message: String = validation_error(&pyramid, &teams);
+
+    assert!(message.contains("team 5 is in more than one division"), "{}", message);
+    assert!(message.contains("Second has unknown team 11"), "{}", message);
+    assert!(message.contains("team 6 (Team 6) isn't in any division"), "{}", message);
+}
+
+#[test]
+fn validate_reports_bad_movements()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(10);
+
+    let mut missing: Pyramid = pyramid();
+    missing.movements.clear();
+    assert!(validation_error(&missing, &teams).contains("2 divisions need 1 movements, got 0"));
+
+    let mut uneven: Pyramid = pyramid();
+    uneven.movements[0].promoted = 2;
+    assert!(validation_error(&uneven, &teams).contains("1 relegated but 2 promoted"));
+
+    let mut overlapping: Pyramid = pyramid();
+    overlapping.movements[0].playoff = Some(Playoff { upper_places: vec![5], lower_places: vec![2], spots: 1 });
+    assert!(validation_error(&overlapping, &teams).contains("Top: place 5 can't be used"));
+
+    let mut off_the_table: Pyramid = pyramid();
+    off_the_table.movements[0].playoff = Some(Playoff { upper_places: vec![4], lower_places: vec![6], spots: 1 });
+    assert!(validation_error(&off_the_table, &teams).contains("Second: place 6 can't be used"));
+
+    let mut uneven_bracket: Pyramid = pyramid();
+    uneven_bracket.movements[0].playoff = Some(Playoff { upper_places: vec![4], lower_places: vec![2, 3], spots: 1 });
+    assert!(validation_error(&uneven_bracket, &teams).contains("3 playoff teams can't be knocked out down to 1"));
+}
+
+#[test]
+fn division_sizes_stay_the_same_after_movement()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(10);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams);
+    let pyramid: Pyramid = pyramid();
+
+    for seed in 0..3
+    {
+        let season: PyramidSeason = divisions::play_season(&pyramid, &teams, &strengths, &EngineConfig::default(), &DEFAULT_TIEBREAKERS, seed).unwrap();
+
+        let sizes: Vec<usize> = season.next.divisions.iter().map(|division| division.team_ids.len()).collect();
+        assert_eq!(sizes, vec![5, 5]);
+        assert!(season.next.validate(&teams).is_ok());
+
+        //The bottom team goes down for the second's champion, the playoff swaps at most one more pair.
+        let top: HashSet<u32> = season.next.divisions[0].team_ids.iter().copied().collect();
+        assert!(!top.contains(&season.divisions[0].table.rows[4].team_id));
+        assert!(top.contains(&season.divisions[1].table.rows[0].team_id));
+        assert!(season.moves.len() == 2 || season.moves.len() == 4);
+    }
+}
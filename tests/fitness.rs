@@ -0,0 +1,56 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use soccer_sim::fitness::Fitness;
+use soccer_sim::side::Side;
+use soccer_sim::entities::TeamWithPlayers;
+
+/// Fitness of `team` after its starters played a full match.
+fn after_a_match(team: &TeamWithPlayers) -> (Side, Fitness)
+{
+    let mut fitness: Fitness = Fitness::default();
+    let mut side: Side = Side::new(team, &[], &fitness);
+
+    for _ in 0..90
+    {
+        side.tire();
+    }
+
+    fitness.record(&side);
+
+    return (side, fitness);
+}
+
+#[test]
+fn players_recover_between_matchdays()
+{
+    let mut teams: Vec<TeamWithPlayers> = common::teams(1);
+    let (side, mut fitness) = after_a_match(&teams[0]);
+    let starter: u32 = side.on_pitch[5].id;
+    let tired: f32 = fitness.fatigue(starter);
+
+    assert!(tired > 0.5);
+    assert_eq!(fitness.fatigue(side.bench[0].id), 0.0);
+
+    fitness.recover(&teams, 2, 0.12);
+    let rested: f32 = fitness.fatigue(starter);
+    assert!(rested > 0.0 && rested < tired);
+
+    //A player who starts the next match still tired starts it that tired.
+    assert_eq!(Side::new(&teams[0], &[], &fitness).fatigue_of(starter), rested);
+
+    fitness.recover(&teams, 14, 0.12);
+    assert_eq!(fitness.fatigue(starter), 0.0);
+
+    //More stamina means a quicker recovery.
+    let (_, mut fresh_legs) = after_a_match(&teams[0]);
+    let (_, mut heavy_legs) = after_a_match(&teams[0]);
+    let mut weak: Vec<TeamWithPlayers> = teams.clone();
+    weak[0].players.iter_mut().for_each(|player| player.attributes.stamina = 40);
+    teams[0].players.iter_mut().for_each(|player| player.attributes.stamina = 90);
+
+    fresh_legs.recover(&teams, 2, 0.12);
+    heavy_legs.recover(&weak, 2, 0.12);
+    assert!(fresh_legs.fatigue(starter) < heavy_legs.fatigue(starter));
+}
@@ -0,0 +1,240 @@
+#![allow(clippy::needless_return)]
+
+use std::path::{ Path, PathBuf };
+use std::time::{ Duration, Instant };
+
+use soccer_sim::crawler::CrawlConfig;
+use soccer_sim::crawler::fetch::{ Fetcher, HttpFetcher };
+use soccer_sim::crawler::robots::Robots;
+use soccer_sim::crawler::stub::StubServer;
+
+fn fixtures() -> PathBuf
+{
+    return Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pages");
+}
+
+/// An empty scratch directory for one test.
+fn scratch_dir(name: &str) -> PathBuf
+{
+    let dir: PathBuf = std::env::temp_dir().join(format!("soccer-sim-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    return dir;
+}
+
+/// The fixture pages with a robots.txt next to them.
+fn site_with_robots(name: &str, robots: &str) -> PathBuf
+{
+    let dir: PathBuf = scratch_dir(name);
+
+    for page in ["1.html", "2.html"]
+    {
+        std::fs::copy(fixtures().join(page), dir.join(page)).unwrap();
+    }
+
+    std::fs::write(dir.join("robots.txt"), robots).unwrap();
+
+    return dir;
+}
+
+/// Config for a fast crawl of `server` that doesn't look at robots.txt.
+fn config(server: &StubServer) -> CrawlConfig
+{
+    return CrawlConfig { base_url: server.url(), delay_ms: 0, backoff_ms: 1, respect_robots: false, ..Default::default() };
+}
+
+fn statuses(server: &StubServer) -> Vec<(String, u16)>
+{
+    return server.requests().into_iter().map(|request| (request.path, request.status)).collect();
+}
+
+#[test]
+fn retries_server_errors()
+{
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    server.fail_next(2);
+
+    let mut fetcher = HttpFetcher::new(&config(&server)).unwrap();
+
+    assert!(fetcher.fetch_page(1).unwrap().is_some());
+    assert_eq!(statuses(&server), vec![("/1".to_owned(), 503), ("/1".to_owned(), 503), ("/1".to_owned(), 200)]);
+}
+
+#[test]
+fn gives_up_after_the_last_retry()
+{
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    server.fail_next(10);
+
+    let mut fetcher = HttpFetcher::new(&CrawlConfig { retries: 2, ..config(&server) }).unwrap();
+
+    assert!(fetcher.fetch_page(1).is_err());
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn times_out_slow_answers()
+{
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    server.set_delay(Duration::from_millis(400));
+
+    let mut fetcher = HttpFetcher::new(&CrawlConfig { timeout_ms: 100, retries: 0, ..config(&server) }).unwrap();
+
+    assert!(fetcher.fetch_page(1).is_err());
+}
+
+#[test]
+fn missing_page_is_none()
+{
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    let mut fetcher = HttpFetcher::new(&config(&server)).unwrap();
+
+    assert_eq!(fetcher.fetch_page(3).unwrap(), None);
+}
+
+#[test]
+fn waits_between_requests()
+{
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    let mut fetcher = HttpFetcher::new(&CrawlConfig { delay_ms: 150, ..config(&server) }).unwrap();
+
+    let start: Instant = Instant::now();
+    fetcher.fetch_page(1).unwrap();
+    fetcher.fetch_page(2).unwrap();
+
+    assert!(start.elapsed() >= Duration::from_millis(150));
+}
+
+#[test]
+fn sends_the_user_agent()
+{
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    let mut fetcher = HttpFetcher::new(&CrawlConfig { user_agent: "fixture-bot/2.0".to_owned(), ..config(&server) }).unwrap();
+
+    fetcher.fetch_page(1).unwrap();
+
+    assert_eq!(server.requests()[0].user_agent.as_deref(), Some("fixture-bot/2.0"));
+}
+
+#[test]
+fn does_not_fetch_pages_robots_txt_disallows()
+{
+    let site: PathBuf = site_with_robots("robots", "User-agent: *\nDisallow: /2\n");
+    let server: StubServer = StubServer::start(&site).unwrap();
+    let mut fetcher = HttpFetcher::new(&CrawlConfig { respect_robots: true, ..config(&server) }).unwrap();
+
+    assert!(fetcher.fetch_page(1).unwrap().is_some());
+    assert!(fetcher.fetch_page(2).is_err());
+
+    //robots.txt is read once, the disallowed page never asked for.
+    assert_eq!(statuses(&server), vec![("/robots.txt".to_owned(), 200), ("/1".to_owned(), 200)]);
+}
+
+#[test]
+fn missing_robots_txt_allows_everything()
+{
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    let mut fetcher = HttpFetcher::new(&CrawlConfig { respect_robots: true, ..config(&server) }).unwrap();
+
+    assert!(fetcher.fetch_page(1).unwrap().is_some());
+    assert_eq!(statuses(&server), vec![("/robots.txt".to_owned(), 404), ("/1".to_owned(), 200)]);
+}
+
+#[test]
+fn robots_txt_server_error_disallows_everything()
+{
+    let site: PathBuf = site_with_robots("robots-error", "User-agent: *\nAllow: /\n");
+    let server: StubServer = StubServer::start(&site).unwrap();
+    server.fail_next(1);
+
+    let mut fetcher = HttpFetcher::new(&CrawlConfig { respect_robots: true, retries: 0, ..config(&server) }).unwrap();
+
+    assert!(fetcher.fetch_page(1).is_err());
+    assert!(fetcher.fetch_page(2).is_err());
+    assert_eq!(statuses(&server), vec![("/robots.txt".to_owned(), 503)]);
+}
+
+#[test]
+fn robots_txt_timeout_disallows_everything()
+{
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    server.set_delay(Duration::from_millis(400));
+
+    let mut fetcher = HttpFetcher::new(&CrawlConfig { respect_robots: true, timeout_ms: 100, retries: 0, ..config(&server) }).unwrap();
+
+    assert!(fetcher.fetch_page(1).is_err());
+    assert!(server.requests().iter().all(|request| request.path == "/robots.txt"));
+}
+
+#[test]
+fn waits_as_long_as_retry_after_asks()
+{
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    server.set_retry_after("1");
+    server.fail_next(1);
+
+    let mut fetcher = HttpFetcher::new(&config(&server)).unwrap();
+    let start: Instant = Instant::now();
+
+    assert!(fetcher.fetch_page(1).unwrap().is_some());
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert_eq!(statuses(&server), vec![("/1".to_owned(), 503), ("/1".to_owned(), 200)]);
+}
+
+#[test]
+fn gives_up_when_retry_after_is_too_long()
+{
+    for retry_after in ["3600", "Wed, 21 Oct 2099 07:28:00 GMT"]
+    {
+        let server: StubServer = StubServer::start(&fixtures()).unwrap();
+        server.set_retry_after(retry_after);
+        server.fail_next(1);
+
+        let mut fetcher = HttpFetcher::new(&config(&server)).unwrap();
+
+        assert!(fetcher.fetch_page(1).is_err(), "{}", retry_after);
+        assert_eq!(server.requests().len(), 1, "{}", retry_after);
+    }
+}
+
+#[test]
+fn revalidates_cached_pages()
+{
+    let cache_dir: PathBuf = scratch_dir("cache");
+    let server: StubServer = StubServer::start(&fixtures()).unwrap();
+    let config = CrawlConfig { cache_dir: Some(cache_dir), ..config(&server) };
+
+    let first: Option<String> = HttpFetcher::new(&config).unwrap().fetch_page(1).unwrap();
+    let second: Option<String> = HttpFetcher::new(&config).unwrap().fetch_page(1).unwrap();
+
+    assert!(first.is_some());
+    assert_eq!(first, second);
+    assert_eq!(statuses(&server), vec![("/1".to_owned(), 200), ("/1".to_owned(), 304)]);
+}
+
+#[test]
+fn robots_picks_the_group_for_the_user_agent()
+{
+    let text = "User-agent: *\nDisallow: /\n\nUser-agent: other-bot\nUser-agent: soccer-sim\nDisallow: /private\nAllow: /private/players\nCrawl-delay: 2\n";
+    let robots: Robots = Robots::parse(text, "soccer-sim/0.1.0");
+
+    assert!(robots.allows("/players/1"));
+    assert!(!robots.allows("/private/clubs"));
+    assert!(robots.allows("/private/players/2"));
+    assert_eq!(robots.crawl_delay, Some(Duration::from_secs(2)));
+
+    assert!(!Robots::parse(text, "someone-else/1.0").allows("/players/1"));
+}
+
+#[test]
+fn robots_patterns_support_wildcards_and_anchors()
+{
+    let robots: Robots = Robots::parse("User-agent: *\nDisallow: /*.php$\nDisallow: /search*page=\nDisallow:\n", "soccer-sim/0.1.0");
+
+    assert!(!robots.allows("/index.php"));
+    assert!(robots.allows("/index.php?page=2"));
+    assert!(!robots.allows("/search?q=ajax&page=2"));
+    assert!(robots.allows("/search?q=ajax"));
+    assert!(Robots::allow_all().allows("/anything"));
+}
@@ -0,0 +1,160 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use soccer_sim::entities::{ Attributes, Player, Position, TeamWithPlayers };
+use soccer_sim::fitness::Fitness;
+use soccer_sim::lineup::{ self, Lineup };
+use soccer_sim::side::{ MAX_SUBSTITUTIONS, MAX_SUBSTITUTION_WINDOWS, Side };
+use soccer_sim::tactics::Formation;
+
+fn player(id: u32, position: Position, quality: u8) -> Player
+{
+    let attributes: Attributes = Attributes::from_quality(quality, &position, 70);
+
+    return Player { id, name: format!("Player {}", id), position, attributes, ..Default::default() };
+}
+
+fn count(players: &[Player], position: Position) -> usize
+{
+    return players.iter().filter(|player| player.position == position).count();
+}
+
+#[test]
+fn the_lineup_takes_the_best_players_for_every_line()
+{
+    let team: TeamWithPlayers = common::teams(1).remove(0);
+    let Lineup { starters, bench } = lineup::pick_lineup(&team.players, Formation::F442, &[]);
+
+    assert_eq!(starters.len(), 11);
+    assert_eq!(starters.len() + bench.len(), team.players.len());
+    assert_eq!(count(&starters, Position::Goalkeeper), 1);
+    assert_eq!(count(&starters, Position::Defender), 4);
+    assert_eq!(count(&starters, Position::Midfield), 4);
+    assert_eq!(count(&starters, Position::Attacker), 2);
+
+    //Nobody on the bench is better than a starter in the same position.
+    for starter in &starters
+    {
+        assert!(bench.iter().filter(|player| player.position == starter.position).all(|player| player.rating() <= starter.rating()));
+    }
+}
+
+#[test]
+fn the_lineup_leaves_out_unavailable_players_and_tops_up_short_lines()
+{
+    let mut players: Vec<Player> = vec![player(1, Position::Goalkeeper, 60), player(2, Position::Goalkeeper, 50)];
+    players.extend((3..8).map(|id| player(id, Position::Defender, 60)));
+    players.extend((8..13).map(|id| player(id, Position::Midfield, 50 + id as u8)));
+    players.push(player(13, Position::Attacker, 70));
+    players.push(player(14, Position::Goalkeeper, 95));
+
+    let Lineup { starters, bench } = lineup::pick_lineup(&players, Formation::F433, &[1, 3]);
+    let ids: Vec<u32> = starters.iter().map(|player| player.id).collect();
+
+    assert_eq!(starters.len(), 11);
+    assert!(!ids.contains(&1) && !ids.contains(&3) && bench.iter().all(|player| player.id != 1 && player.id != 3));
+    assert_eq!(starters[0].id, 14);
+
+    //Two missing attackers are replaced by the best outfield players left, never by a keeper.
+    assert_eq!(count(&starters, Position::Attacker), 1);
+    assert_eq!(count(&starters, Position::Midfield), 5);
+    assert_eq!(bench.iter().map(|player| player.id).collect::<Vec<u32>>(), vec![2]);
+}
+
+#[test]
+fn the_best_replacement_plays_the_same_position_when_possible()
+{
+    let bench: Vec<Player> = vec![player(1, Position::Defender, 80), player(2, Position::Midfield, 60), player(3, Position::Midfield, 65), player(4, Position::Goalkeeper, 90)];
+
+    assert_eq!(lineup::best_replacement(&bench, &Position::Midfield), Some(2));
+    assert_eq!(lineup::best_replacement(&bench, &Position::Goalkeeper), Some(3));
+
+    //Nobody plays up front, so the best outfield player comes on, never the keeper.
+    assert_eq!(lineup::best_replacement(&bench, &Position::Attacker), Some(0));
+    assert_eq!(lineup::best_replacement(&bench[..3], &Position::Goalkeeper), None);
+    assert_eq!(lineup::best_replacement(&[], &Position::Defender), None);
+}
+
+#[test]
+fn no_more_than_five_substitutions_are_made()
+{
+    let team: TeamWithPlayers = common::teams(1).remove(0);
+    let mut side: Side = Side::new(&team, &[], &Fitness::default());
+
+    side.fatigue = vec![1.0; side.on_pitch.len()];
+    assert_eq!(side.substitute(60, 0, 0).len(), MAX_SUBSTITUTIONS as usize);
+
+    //The sixth exhausted player has to stay on.
+    side.start_minute();
+    side.fatigue = vec![1.0; side.on_pitch.len()];
+    assert!(side.substitute(70, 0, 0).is_empty());
+    assert_eq!(side.substitutions_left, 0);
+}
+
+#[test]
+fn no_more_than_three_substitution_windows_are_used()
+{
+    let team: TeamWithPlayers = common::teams(1).remove(0);
+    let mut side: Side = Side::new(&team, &[], &Fitness::default());
+
+    for (window, minute) in [55, 65, 75].iter().enumerate()
+    {
+        side.start_minute();
+        side.fatigue[window + 1] = 1.0;
+        assert_eq!(side.substitute(*minute, 0, 0).len(), 1);
+    }
+
+    //Changes are left, but a fourth window isn't.
+    side.start_minute();
+    side.fatigue[4] = 1.0;
+    assert!(side.substitute(85, 0, 0).is_empty());
+    assert_eq!(side.substitutions_left, MAX_SUBSTITUTIONS - MAX_SUBSTITUTION_WINDOWS);
+    assert_eq!(side.windows_left, 0);
+}
+
+#[test]
+fn changes_forced_in_the_same_minute_share_a_window()
+{
+    let team: TeamWithPlayers = common::teams(1).remove(0);
+    let mut side: Side = Side::new(&team, &[], &Fitness::default());
+
+    //Two injuries in one minute, after a tired player was already taken off.
+    side.start_minute();
+    side.fatigue[1] = 1.0;
+    assert_eq!(side.substitute(60, 0, 0).len(), 1);
+    assert!(side.injure(2).is_some());
+    assert!(side.injure(3).is_some());
+    assert_eq!((side.substitutions_left, side.windows_left), (MAX_SUBSTITUTIONS - 3, MAX_SUBSTITUTION_WINDOWS - 1));
+
+    side.start_minute();
+    assert!(side.injure(4).is_some());
+    side.start_minute();
+    assert!(side.injure(5).is_some());
+    assert_eq!((side.substitutions_left, side.windows_left), (0, 0));
+    assert_eq!(side.on_pitch.len(), 11);
+}
+
+#[test]
+fn the_last_window_stays_open_for_the_rest_of_its_minute()
+{
+    let team: TeamWithPlayers = common::teams(1).remove(0);
+    let mut side: Side = Side::new(&team, &[], &Fitness::default());
+
+    for index in 1..3
+    {
+        side.start_minute();
+        assert!(side.injure(index).is_some());
+    }
+
+    side.start_minute();
+    assert!(side.injure(3).is_some());
+    assert!(side.injure(4).is_some());
+    assert_eq!(side.windows_left, 0);
+
+    //The next minute there's no window left, the team plays on a man down.
+    side.start_minute();
+    assert!(side.injure(5).is_none());
+    assert_eq!(side.on_pitch.len(), 10);
+    assert_eq!(side.substitutions_left, 1);
+}
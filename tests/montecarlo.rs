@@ -0,0 +1,27 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use soccer_sim::entities::{ TeamStrength, TeamWithPlayers };
+use soccer_sim::montecarlo::{ self, Projection, ProjectionConfig };
+
+fn project(teams: &[TeamWithPlayers], strengths: &[TeamStrength], threads: usize) -> String
+{
+    let config = ProjectionConfig { runs: 12, threads, ..Default::default() };
+    let projection: Projection = montecarlo::project(teams, strengths, 42, &config).unwrap();
+
+    return serde_json::to_string(&projection).unwrap();
+}
+
+#[test]
+fn projection_does_not_depend_on_the_thread_count()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(4);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams);
+    let single: String = project(&teams, &strengths, 1);
+
+    for threads in [2, 3, 5, 16]
+    {
+        assert_eq!(project(&teams, &strengths, threads), single, "{} threads", threads);
+    }
+}
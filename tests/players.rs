@@ -0,0 +1,81 @@
+#![allow(clippy::needless_return)]
+
+use chrono::NaiveDate;
+
+use soccer_sim::entities::{ self, Attributes, DEFAULT_STAMINA, Player };
+
+/// The attributes as (finishing, passing, tackling, pace, goalkeeping, stamina).
+fn values(attributes: &Attributes) -> (u8, u8, u8, u8, u8, u8)
+{
+    return (attributes.finishing, attributes.passing, attributes.tackling, attributes.pace, attributes.goalkeeping, attributes.stamina);
+}
+
+#[test]
+fn a_player_with_only_a_quality_gets_it_for_every_attribute()
+{
+    let player: Player = serde_json::from_str(r#"{"id":4,"name":"Old File","height":"1,80 m","position":"Attacker","quality":66,"stamina":81}"#).unwrap();
+
+    assert_eq!(values(&player.attributes), (66, 66, 66, 66, 20, 81));
+}
+
+#[test]
+fn a_player_without_a_stamina_gets_the_default()
+{
+    let player: Player = serde_json::from_str(r#"{"id":4,"name":"Old File","height":"1,80 m","position":"Defender","quality":58}"#).unwrap();
+
+    assert_eq!(values(&player.attributes), (58, 58, 58, 58, 20, DEFAULT_STAMINA));
+}
+
+#[test]
+fn a_keeper_with_only_a_quality_keeps_it_in_goal()
+{
+    let player: Player = serde_json::from_str(r#"{"id":1,"name":"Old Keeper","height":"1,90 m","position":"Goalkeeper","quality":74}"#).unwrap();
+
+    assert_eq!(values(&player.attributes), (74, 74, 74, 74, 74, DEFAULT_STAMINA));
+}
+
+#[test]
+fn a_player_needs_attributes_or_a_quality()
+{
+    let result = serde_json::from_str::<Player>(r#"{"id":9,"name":"Nothing","height":"1,75 m","position":"Midfield"}"#);
+
+    assert!(result.unwrap_err().to_string().contains("player 9 has neither attributes nor a quality"));
+}
+
+#[test]
+fn attributes_win_over_a_leftover_quality()
+{
+    let json = r#"{"id":2,"name":"New File","height":"1,85 m","position":"Midfield","quality":40,
+        "attributes":{"finishing":61,"passing":72,"tackling":55,"pace":68,"goalkeeping":12,"stamina":77}}"#;
+    let player: Player = serde_json::from_str(json).unwrap();
+
+    assert_eq!(values(&player.attributes), (61, 72, 55, 68, 12, 77));
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate
+{
+    return NaiveDate::from_ymd_opt(year, month, day).unwrap();
+}
+
+#[test]
+fn dates_of_birth_parse_as_crawled_and_as_stored()
+{
+    assert_eq!(entities::parse_dob("21.07.1992"), Some(date(1992, 7, 21)));
+    assert_eq!(entities::parse_dob(" 1992-07-21 "), Some(date(1992, 7, 21)));
+
+    for garbage in ["", "???", "unbekannt", "31.02.1990", "1992/07/21", "21.07.", "99999999999999999999"]
+    {
+        assert_eq!(entities::parse_dob(garbage), None, "{:?}", garbage);
+    }
+}
+
+#[test]
+fn a_player_is_a_year_older_from_their_birthday_on()
+{
+    let player = Player { dob: Some(date(1992, 7, 21)), ..Default::default() };
+
+    assert_eq!(player.age_on(date(2021, 7, 20)), Some(28));
+    assert_eq!(player.age_on(date(2021, 7, 21)), Some(29));
+    assert_eq!(player.age_on(date(1990, 1, 1)), Some(0));
+    assert_eq!(Player::default().age_on(date(2021, 7, 21)), None);
+}
@@ -0,0 +1,58 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::collections::HashSet;
+
+use soccer_sim::entities::{ Match, TeamWithPlayers };
+use soccer_sim::simulation;
+
+#[test]
+fn every_team_plays_once_per_matchday()
+{
+    for count in 2..=9
+    {
+        let teams: Vec<TeamWithPlayers> = common::teams(count);
+        let matches: Vec<Match> = simulation::make_matches(&teams);
+        let rounds: u32 = if count % 2 == 0 { count - 1 } else { count };
+
+        assert_eq!(matches.len() as u32, count * (count - 1));
+        assert_eq!(matches.iter().map(|game| game.matchday).max(), Some(rounds * 2));
+
+        for matchday in 1..=rounds * 2
+        {
+            let mut playing: HashSet<u32> = HashSet::new();
+
+            for game in matches.iter().filter(|game| game.matchday == matchday)
+            {
+                assert!(playing.insert(game.team_home), "team {} plays twice on matchday {}", game.team_home, matchday);
+                assert!(playing.insert(game.team_out), "team {} plays twice on matchday {}", game.team_out, matchday);
+            }
+
+            //Only the team on a bye sits out.
+            assert_eq!(playing.len() as u32, count - count % 2);
+        }
+    }
+}
+
+#[test]
+fn second_half_mirrors_the_first()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(6);
+    let matches: Vec<Match> = simulation::make_matches(&teams);
+    let (first, second) = matches.split_at(matches.len() / 2);
+
+    for (game, mirror) in first.iter().zip(second)
+    {
+        assert_eq!(mirror.matchday, game.matchday + 5);
+        assert_eq!((mirror.team_home, mirror.team_out), (game.team_out, game.team_home));
+    }
+}
+
+#[test]
+fn matches_without_a_matchday_still_load()
+{
+    let game: Match = serde_json::from_str(r#"{"team_home":1,"team_out":2,"goals":[]}"#).unwrap();
+
+    assert_eq!((game.matchday, game.team_home, game.team_out), (0, 1, 2));
+}
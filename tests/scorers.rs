@@ -0,0 +1,85 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::collections::HashMap;
+
+use soccer_sim::config::{ EngineConfig, PositionWeights };
+use soccer_sim::entities::{ Goal, Match, Position, TeamStrength, TeamWithPlayers };
+use soccer_sim::simulation;
+
+/// The goals of `count` seeded friendlies between teams 1 and 2.
+fn goals(teams: &[TeamWithPlayers], config: &EngineConfig, count: u64) -> Vec<Goal>
+{
+    let strengths: Vec<TeamStrength> = common::strengths(teams);
+
+    return (0..count)
+        .flat_map(|seed| {
+            let game: Match = simulation::play_match(teams, &strengths, config, 1, 2, seed).unwrap();
+            return game.goals;
+        })
+        .collect();
+}
+
+fn positions(teams: &[TeamWithPlayers]) -> HashMap<u32, Position>
+{
+    return teams.iter()
+        .flat_map(|team| team.players.iter())
+        .map(|player| (player.id, player.position.clone()))
+        .collect();
+}
+
+#[test]
+fn a_keeper_with_no_scorer_weight_never_scores()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(2);
+    let positions: HashMap<u32, Position> = positions(&teams);
+    let config = EngineConfig { scorer_weights: PositionWeights { attacker: 1.0, midfield: 1.0, defender: 1.0, goalkeeper: 0.0 }, ..Default::default() };
+    let goals: Vec<Goal> = goals(&teams, &config, 100);
+
+    assert!(!goals.is_empty());
+    assert!(goals.iter().all(|goal| positions[&goal.player_id] != Position::Goalkeeper));
+}
+
+#[test]
+fn nobody_assists_their_own_goal()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(2);
+    let config = EngineConfig { assist_chance: 1.0, ..Default::default() };
+    let goals: Vec<Goal> = goals(&teams, &config, 100);
+
+    assert!(!goals.is_empty());
+
+    for goal in &goals
+    {
+        let assist: u32 = goal.assist_player_id.expect("every goal is assisted");
+        let squad: &TeamWithPlayers = teams.iter().find(|team| team.team.id == goal.team_id).unwrap();
+
+        assert_ne!(assist, goal.player_id);
+        assert!(squad.players.iter().any(|player| player.id == assist));
+    }
+}
+
+#[test]
+fn scorers_follow_the_position_weights()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(2);
+    let positions: HashMap<u32, Position> = positions(&teams);
+
+    //Odds of a midfielder rather than an attacker scoring.
+    let midfield_odds = |midfield: f32| {
+        let config = EngineConfig { scorer_weights: PositionWeights { attacker: 1.0, midfield, defender: 0.0, goalkeeper: 0.0 }, ..Default::default() };
+        let goals: Vec<Goal> = goals(&teams, &config, 200);
+        let midfield_goals = goals.iter().filter(|goal| positions[&goal.player_id] == Position::Midfield).count() as f32;
+        let attacker_goals = goals.iter().filter(|goal| positions[&goal.player_id] == Position::Attacker).count() as f32;
+
+        assert_eq!((midfield_goals + attacker_goals) as usize, goals.len());
+        return midfield_goals / attacker_goals;
+    };
+
+    assert_eq!(midfield_odds(0.0), 0.0);
+
+    //Four times the weight should give about four times the odds.
+    let ratio: f32 = midfield_odds(4.0) / midfield_odds(1.0);
+    assert!((3.0..=5.5).contains(&ratio), "ratio {}", ratio);
+}
@@ -0,0 +1,265 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use soccer_sim::config::EngineConfig;
+use soccer_sim::entities::{ EventKind, Goal, Match, TeamStrength, TeamWithPlayers };
+use soccer_sim::error::Error;
+use soccer_sim::{ entities, simulation };
+
+fn season_json(teams: &[TeamWithPlayers], strengths: &[TeamStrength], seed: u64) -> String
+{
+    let season = simulation::simulate(teams, strengths, &EngineConfig::default(), seed).unwrap();
+
+    return serde_json::to_string(&season).unwrap();
+}
+
+#[test]
+fn a_season_is_reproducible_from_its_seed()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(4);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams);
+    let first: String = season_json(&teams, &strengths, 11);
+
+    assert_eq!(season_json(&teams, &strengths, 11), first);
+    assert_ne!(season_json(&teams, &strengths, 12), first);
+}
+
+#[test]
+fn the_event_log_covers_both_halves_and_every_goal()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(2);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams);
+    let mut goals_seen: usize = 0;
+
+    for seed in 0..20
+    {
+        let game: Match = simulation::play_match(&teams, &strengths, &EngineConfig::default(), 1, 2, seed).unwrap();
+
+        if game.forfeited_by.is_some()
+        {
+            continue;
+        }
+
+        let first = &game.events[0];
+        assert_eq!((first.minute, first.kind, first.team_id), (0, EventKind::KickOff, 1));
+        assert!(game.events.iter().any(|event| event.minute == 45 && event.added_time == 0 && event.kind == EventKind::KickOff && event.team_id == 2));
+        assert!(game.events.iter().any(|event| event.minute == 90));
+
+        //Every goal has an event in the same minute for the same team and scorer, in order.
+        let goal_events: Vec<_> = game.events.iter().filter(|event| event.kind == EventKind::Goal).collect();
+        assert_eq!(goal_events.len(), game.goals.len());
+
+        for (event, goal) in goal_events.iter().zip(&game.goals)
+        {
+            assert_eq!((event.minute, event.added_time, event.team_id), (goal.time, goal.added_time, goal.team_id));
+            assert_eq!(event.player_ids[0], goal.player_id);
+        }
+
+        let home_events: u32 = goal_events.iter().filter(|event| event.team_id == 1).count() as u32;
+        assert_eq!((home_events, goal_events.len() as u32 - home_events), game.score());
+        goals_seen += goal_events.len();
+    }
+
+    assert!(goals_seen > 0);
+}
+
+#[test]
+fn a_team_cant_play_itself()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(2);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams);
+
+    let result = simulation::play_match(&teams, &strengths, &EngineConfig::default(), 1, 1, 7);
+
+    assert!(matches!(result, Err(Error::Validation(_))));
+    assert!(simulation::play_match(&teams, &strengths, &EngineConfig::default(), 1, 2, 7).is_ok());
+}
+
+#[test]
+fn a_team_down_to_six_players_forfeits()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(2);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams);
+    let config = EngineConfig { red_card_chance: 0.5, ..Default::default() };
+
+    for seed in 0..20
+    {
+        let game: Match = simulation::play_match(&teams, &strengths, &config, 1, 2, seed).unwrap();
+        let short_team: u32 = game.forfeited_by.expect("a red card every other minute leaves a team short");
+        let sent_off: usize = game.cards.iter().filter(|card| card.team_id == short_team && card.is_sending_off()).count();
+        let play: Vec<(EventKind, u32)> = game.events.iter()
+            .filter(|event| event.kind != EventKind::Fatigue)
+            .map(|event| (event.kind, event.team_id))
+            .collect();
+
+        //Nothing happens after the match is abandoned.
+        assert_eq!(play.last(), Some(&(EventKind::Abandoned, short_team)));
+        assert!(sent_off > 0);
+        assert_eq!(game.winner(), Some(if short_team == 1 { 2 } else { 1 }));
+
+        //A knockout match ends there too.
+        let knockout: Match = simulation::play_knockout_match(&teams, &strengths, &config, 1, 2, (0, 0), seed).unwrap();
+        assert!(knockout.forfeited_by.is_some() && !knockout.extra_time && knockout.shootout.is_none());
+    }
+}
+
+#[test]
+fn forfeits_are_awarded_three_nil_unless_the_score_was_worse()
+{
+    let goal = |team_id: u32| Goal { team_id, ..Default::default() };
+    let mut game = Match { team_home: 1, team_out: 2, goals: vec![goal(1)], forfeited_by: Some(1), ..Default::default() };
+
+    assert_eq!(game.score(), (0, 3));
+
+    game.goals = vec![goal(2), goal(2), goal(2), goal(2)];
+    assert_eq!(game.score(), (0, 4));
+
+    game.forfeited_by = Some(2);
+    assert_eq!(game.score(), (3, 0));
+}
+
+/// Knockout matches between two even teams that went to penalties.
+fn shootouts() -> Vec<Match>
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(2);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams);
+
+    return (0..400)
+        .map(|seed| simulation::play_knockout_match(&teams, &strengths, &EngineConfig::default(), 1, 2, (0, 0), seed).unwrap())
+        .filter(|game| game.shootout.is_some())
+        .collect();
+}
+
+/// Goals of (home, out) after each kick of a shootout.
+fn running_score(game: &Match) -> Vec<(u32, u32)>
+{
+    let mut score: (u32, u32) = (0, 0);
+
+    return game.shootout.as_ref().unwrap().kicks.iter().map(|kick| {
+        if kick.scored && kick.team_id == game.team_home { score.0 += 1; }
+        if kick.scored && kick.team_id == game.team_out { score.1 += 1; }
+        return score;
+    }).collect();
+}
+
+#[test]
+fn shootouts_stop_once_a_team_cant_catch_up()
+{
+    let games: Vec<Match> = shootouts();
+    let mut early_stops: u32 = 0;
+
+    for game in &games
+    {
+        let score: Vec<(u32, u32)> = running_score(game);
+        let kicks: Vec<u32> = game.shootout.as_ref().unwrap().kicks.iter().map(|kick| kick.team_id).collect();
+
+        //Home kicks first, then the teams take turns.
+        assert!(kicks.iter().enumerate().all(|(index, team_id)| *team_id == if index % 2 == 0 { 1 } else { 2 }));
+
+        //Within the first five each, a team that can't be caught any more ends it.
+        for (index, (home, out)) in score.iter().enumerate().take(10)
+        {
+            let home_left: u32 = (9 - index as u32) / 2;
+            let out_left: u32 = (10 - index as u32) / 2;
+            let decided: bool = home + home_left < *out || out + out_left < *home;
+
+            assert_eq!(decided, index + 1 == score.len(), "kick {} of {:?}", index, score);
+        }
+
+        if score.len() < 10
+        {
+            early_stops += 1;
+        }
+    }
+
+    assert!(early_stops > 0, "no early stop in {} shootouts", games.len());
+}
+
+#[test]
+fn shootouts_go_to_sudden_death_when_level_after_five()
+{
+    let games: Vec<Match> = shootouts();
+    let mut sudden_deaths: u32 = 0;
+
+    for game in &games
+    {
+        let score: Vec<(u32, u32)> = running_score(game);
+        let shootout = game.shootout.as_ref().unwrap();
+
+        assert_ne!(shootout.home_goals, shootout.out_goals);
+        assert_eq!(score.last(), Some(&(shootout.home_goals, shootout.out_goals)));
+        assert!(game.winner().is_some());
+
+        if score.len() > 10
+        {
+            sudden_deaths += 1;
+
+            //Both take their kick every round, and it only ends on an uneven one.
+            assert_eq!(score.len() % 2, 0);
+
+            for round in (10..score.len()).step_by(2)
+            {
+                let (home, out) = score[round - 1];
+                assert_eq!(home, out, "round {} started uneven in {:?}", round / 2, score);
+            }
+
+            let (home, out) = score[score.len() - 1];
+            assert_eq!((home as i32 - out as i32).abs(), 1);
+        }
+    }
+
+    assert!(sudden_deaths > 0, "no sudden death in {} shootouts", games.len());
+}
+
+#[test]
+fn a_knockout_match_with_a_side_too_small_to_play_is_forfeited()
+{
+    let mut teams: Vec<TeamWithPlayers> = common::teams(2);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams);
+    teams[1].players.truncate(1);
+
+    let game: Match = simulation::play_knockout_match(&teams, &strengths, &EngineConfig { red_card_chance: 0.5, ..Default::default() }, 1, 2, (0, 0), 7).unwrap();
+
+    assert_eq!(game.forfeited_by, Some(2));
+    assert!(game.shootout.is_none());
+    assert_eq!(game.winner(), Some(1));
+}
+
+#[test]
+fn match_minutes_show_stoppage_time()
+{
+    assert_eq!(entities::match_minute(0, 0), "1");
+    assert_eq!(entities::match_minute(44, 0), "45");
+    assert_eq!(entities::match_minute(45, 2), "45+2");
+    assert_eq!(entities::match_minute(90, 4), "90+4");
+}
+
+#[test]
+fn stoppage_time_is_added_to_every_half()
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(2);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams);
+
+    for seed in 0..40
+    {
+        let game: Match = simulation::play_knockout_match(&teams, &strengths, &EngineConfig::default(), 1, 2, (0, 0), seed).unwrap();
+        let halves: usize = if game.extra_time { 4 } else { 2 };
+        let half_ends: [u32; 4] = [45, 90, 105, 120];
+
+        assert_eq!(game.stoppage.len(), halves);
+        assert!(game.stoppage.iter().all(|minutes| (1..=10).contains(minutes)));
+
+        //Everything in stoppage time happens at the end of its half, within the minutes added.
+        for event in game.events.iter().filter(|event| event.added_time > 0)
+        {
+            let half: usize = half_ends.iter().position(|end| *end == event.minute).expect("stoppage time at the end of a half");
+            assert!(event.added_time <= game.stoppage[half]);
+        }
+
+        //The home team kicks off the first half, the out team the second.
+        let first_of = |minute: u32| game.events.iter().find(|event| event.minute == minute && event.added_time == 0).map(|event| (event.kind, event.team_id));
+        assert_eq!(first_of(0), Some((EventKind::KickOff, 1)));
+        assert_eq!(first_of(45), Some((EventKind::KickOff, 2)));
+    }
+}
@@ -0,0 +1,68 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::collections::HashSet;
+
+use soccer_sim::entities::{ Player, Team, TeamWithPlayers };
+use soccer_sim::error::Error;
+use soccer_sim::rng;
+use soccer_sim::squad::{ self, SquadConfig, SquadSource };
+
+/// The players and bare teams of `count` full squads.
+fn pool(count: u32) -> (Vec<Player>, Vec<Team>)
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(count);
+    let players: Vec<Player> = teams.iter().flat_map(|team| team.players.clone()).collect();
+
+    return (players, teams.into_iter().map(|team| team.team).collect());
+}
+
+#[test]
+fn deals_every_player_to_one_team_at_most()
+{
+    let (players, teams) = pool(4);
+    let squads = squad::build_squads(&players, &teams, &SquadConfig::default(), SquadSource::Random, &mut rng::from_seed(3)).unwrap();
+
+    let mut dealt: HashSet<u32> = HashSet::new();
+
+    for team in &squads
+    {
+        assert_eq!(team.players.len() as u32, SquadConfig::default().size());
+        assert!(team.players.iter().all(|player| dealt.insert(player.id)));
+    }
+}
+
+#[test]
+fn too_few_players_is_an_error()
+{
+    let (players, mut teams) = pool(4);
+    teams.push(Team { id: 5, name: "Team 5".to_owned(), ..Default::default() });
+
+    let result = squad::build_squads(&players, &teams, &SquadConfig::default(), SquadSource::Random, &mut rng::from_seed(3));
+
+    assert!(matches!(result, Err(Error::Validation(_))));
+}
+
+#[test]
+fn impossible_squad_sizes_are_an_error()
+{
+    let (players, teams) = pool(2);
+    let too_small = SquadConfig { attackers: 1, midfielders: 1, defenders: 1, goalkeepers: 1 };
+    let no_keeper = SquadConfig { goalkeepers: 0, defenders: 9, ..Default::default() };
+
+    for config in [too_small, no_keeper]
+    {
+        let result = squad::build_squads(&players, &teams, &config, SquadSource::Random, &mut rng::from_seed(3));
+        assert!(matches!(result, Err(Error::Validation(_))), "{:?}", config);
+    }
+}
+
+#[test]
+fn real_clubs_without_clubs_is_an_error()
+{
+    let (players, teams) = pool(2);
+    let result = squad::build_squads(&players, &teams, &SquadConfig::default(), SquadSource::RealClubs, &mut rng::from_seed(3));
+
+    assert!(matches!(result, Err(Error::Validation(_))));
+}
@@ -0,0 +1,84 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::path::PathBuf;
+
+use soccer_sim::entities::{ Team, TeamStrength, TeamWithPlayers };
+use soccer_sim::error::Error;
+use soccer_sim::storage;
+
+fn problems(strengths: &[TeamStrength], teams: &[Team]) -> String
+{
+    match TeamStrength::validate(strengths, teams)
+    {
+        Err(Error::Validation(message)) => { return message; },
+        other => { panic!("expected a validation error, got {:?}", other); }
+    }
+}
+
+fn setup() -> (Vec<Team>, Vec<TeamStrength>)
+{
+    let teams: Vec<TeamWithPlayers> = common::teams(3);
+    let strengths: Vec<TeamStrength> = common::strengths(&teams);
+
+    return (teams.into_iter().map(|team| team.team).collect(), strengths);
+}
+
+#[test]
+fn one_strength_per_team_is_valid()
+{
+    let (teams, strengths) = setup();
+
+    assert!(TeamStrength::validate(&strengths, &teams).is_ok());
+}
+
+#[test]
+fn a_team_without_a_strength_is_reported()
+{
+    let (teams, mut strengths) = setup();
+    strengths.retain(|strength| strength.team_id != 2);
+
+    assert!(problems(&strengths, &teams).contains("no strength for team 2 (Team 2)"));
+}
+
+#[test]
+fn a_team_with_two_strengths_is_reported()
+{
+    let (teams, mut strengths) = setup();
+    strengths.push(strengths[0].clone());
+
+    assert!(problems(&strengths, &teams).contains("2 strengths for team 1 (Team 1)"));
+}
+
+#[test]
+fn a_strength_for_an_unknown_team_is_reported()
+{
+    let (teams, mut strengths) = setup();
+    let mut stray: TeamStrength = strengths[0].clone();
+    stray.team_id = 9;
+    strengths.push(stray);
+
+    assert!(problems(&strengths, &teams).contains("strength for unknown team 9"));
+}
+
+#[test]
+fn a_legacy_standings_array_with_an_extra_entry_is_rejected()
+{
+    let dir: PathBuf = std::env::temp_dir().join(format!("soccer-sim-standings-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    //The shipped standings.json had 19 ratings for 18 teams.
+    let teams: Vec<Team> = common::teams(18).into_iter().map(|team| team.team).collect();
+    let ratings: Vec<u32> = (0..19).map(|index| 60 + index).collect();
+    std::fs::write(dir.join("standings.json"), serde_json::to_string(&ratings).unwrap()).unwrap();
+
+    let result = storage::load_strengths(&dir.join("strengths.json"), &dir.join("standings.json"), &teams);
+
+    match result
+    {
+        Err(Error::Validation(message)) => { assert!(message.contains("strength for unknown team 19"), "{}", message); },
+        other => { panic!("expected a validation error, got {:?}", other); }
+    }
+}
@@ -0,0 +1,69 @@
+#![allow(clippy::needless_return)]
+
+use soccer_sim::entities::{ Goal, Match, Team };
+use soccer_sim::table::{ LeagueTable, Tiebreaker, DEFAULT_TIEBREAKERS };
+
+fn teams() -> Vec<Team>
+{
+    return ["Alpha", "Bravo", "Charlie", "Delta"].iter().enumerate()
+        .map(|(index, name)| Team { id: index as u32 + 1, name: name.to_string(), ..Default::default() })
+        .collect();
+}
+
+/// A played match with the given score.
+fn result(team_home: u32, team_out: u32, home_goals: u32, out_goals: u32) -> Match
+{
+    let goal = |team_id: u32| Goal { team_id, ..Default::default() };
+    let mut goals: Vec<Goal> = (0..home_goals).map(|_| goal(team_home)).collect();
+    goals.extend((0..out_goals).map(|_| goal(team_out)));
+
+    return Match { team_home, team_out, goals, ..Default::default() };
+}
+
+fn order(matches: &[Match], tiebreakers: &[Tiebreaker]) -> Vec<String>
+{
+    return LeagueTable::compute(&teams(), matches, tiebreakers).rows.into_iter().map(|row| row.team_name).collect();
+}
+
+#[test]
+fn goal_difference_separates_teams_level_on_points()
+{
+    let matches: Vec<Match> = vec![result(2, 4, 3, 0), result(1, 3, 1, 0)];
+
+    assert_eq!(order(&matches, &[Tiebreaker::GoalDifference]), vec!["Bravo", "Alpha", "Charlie", "Delta"]);
+    //Without tiebreakers teams level on points are ordered by name.
+    assert_eq!(order(&matches, &[]), vec!["Alpha", "Bravo", "Charlie", "Delta"]);
+}
+
+#[test]
+fn goals_scored_separates_teams_level_on_goal_difference()
+{
+    let matches: Vec<Match> = vec![result(2, 4, 4, 2), result(1, 3, 2, 0)];
+
+    assert_eq!(order(&matches, &[Tiebreaker::GoalDifference, Tiebreaker::GoalsScored]), vec!["Bravo", "Alpha", "Delta", "Charlie"]);
+    assert_eq!(order(&matches, &[Tiebreaker::GoalDifference]), vec!["Alpha", "Bravo", "Charlie", "Delta"]);
+}
+
+#[test]
+fn head_to_head_separates_teams_level_on_everything_else()
+{
+    //Alpha and Bravo both end on 4 points with 2 scored and 2 conceded, Bravo won their match.
+    let matches: Vec<Match> = vec![
+        result(2, 1, 1, 0),
+        result(1, 3, 2, 1),
+        result(3, 2, 2, 1),
+        result(1, 4, 0, 0),
+        result(4, 2, 0, 0)
+    ];
+
+    let table: LeagueTable = LeagueTable::compute(&teams(), &matches, &DEFAULT_TIEBREAKERS);
+    let rows: Vec<(String, u32, u32)> = table.rows.iter().map(|row| (row.team_name.clone(), row.position, row.points)).collect();
+
+    assert_eq!(rows, vec![
+        ("Bravo".to_owned(), 1, 4),
+        ("Alpha".to_owned(), 2, 4),
+        ("Charlie".to_owned(), 3, 3),
+        ("Delta".to_owned(), 4, 2)
+    ]);
+    assert_eq!(order(&matches, &[Tiebreaker::GoalDifference, Tiebreaker::GoalsScored]), vec!["Alpha", "Bravo", "Charlie", "Delta"]);
+}
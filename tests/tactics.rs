@@ -0,0 +1,66 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::str::FromStr;
+
+use soccer_sim::entities::TeamWithPlayers;
+use soccer_sim::error::Error;
+use soccer_sim::tactics::{ self, Formation, Tactics, TeamSetup, TeamTactics };
+
+const FORMATIONS: [Formation; 7] = [Formation::F433, Formation::F442, Formation::F451, Formation::F352, Formation::F343, Formation::F532, Formation::F541];
+const TACTICS: [Tactics; 5] = [Tactics::Attacking, Tactics::Balanced, Tactics::Defensive, Tactics::Counter, Tactics::HighPress];
+
+#[test]
+fn formations_parse_from_their_file_names()
+{
+    for formation in FORMATIONS
+    {
+        let name: String = serde_json::from_value(serde_json::to_value(formation).unwrap()).unwrap();
+
+        assert_eq!(Formation::from_str(&name), Ok(formation), "{}", name);
+        assert_eq!(serde_json::from_value::<Formation>(serde_json::Value::String(name)).unwrap(), formation);
+
+        let (defenders, midfielders, attackers) = formation.lines();
+        assert_eq!(defenders + midfielders + attackers, 10);
+    }
+}
+
+#[test]
+fn tactics_parse_from_their_file_names()
+{
+    for tactic in TACTICS
+    {
+        let name: String = serde_json::from_value(serde_json::to_value(tactic).unwrap()).unwrap();
+
+        assert_eq!(Tactics::from_str(&name), Ok(tactic), "{}", name);
+    }
+
+    assert_eq!(Tactics::from_str("high-press"), Ok(Tactics::HighPress));
+}
+
+#[test]
+fn unknown_names_are_rejected()
+{
+    for name in ["", "4-4-3", "442", "4-4-2 ", "2-3-5"]
+    {
+        assert!(Formation::from_str(name).is_err(), "{:?}", name);
+        assert!(serde_json::from_value::<Formation>(serde_json::Value::String(name.to_owned())).is_err());
+    }
+
+    assert!(Tactics::from_str("park-the-bus").is_err());
+}
+
+#[test]
+fn tactics_for_an_unknown_team_are_rejected()
+{
+    let mut teams: Vec<TeamWithPlayers> = common::teams(2);
+    let setup = TeamSetup { formation: Formation::F541, tactics: Tactics::Counter };
+
+    let result = tactics::apply_tactics(&mut teams, &[TeamTactics { team_id: 3, setup }]);
+    assert!(matches!(result, Err(Error::Validation(message)) if message.contains("unknown team 3")));
+
+    tactics::apply_tactics(&mut teams, &[TeamTactics { team_id: 2, setup }]).unwrap();
+    assert_eq!(teams[1].setup.formation, Formation::F541);
+    assert_eq!(teams[0].setup.formation, Formation::default());
+}